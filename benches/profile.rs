@@ -0,0 +1,176 @@
+//! Benchmarks of profile builders.
+//!
+//! The benchmarks rely on the unstable `test` crate and are compiled only with
+//! the `unstable` feature enabled on a nightly toolchain:
+//!
+//! ```text
+//! $ cargo +nightly bench --features unstable
+//! ```
+
+#![cfg(feature = "unstable")]
+#![feature(test)]
+
+extern crate streamer;
+extern crate test;
+
+use std::mem;
+use streamer::platform::{Profile, ProfileBuilder};
+use test::Bencher;
+
+const ELEMENT_COUNT: usize = 64;
+const STEP_COUNT: usize = 10000;
+const SHORT_STEP_COUNT: usize = 1000;
+
+macro_rules! pull(
+    ($bencher:ident, $builder:ident, $step_count:expr) => ({
+        let data = vec![1.0; $step_count];
+        $bencher.iter(|| {
+            let mut builder = $builder::new(ELEMENT_COUNT, 1e-3, vec![0.0; ELEMENT_COUNT]);
+            for i in 0..ELEMENT_COUNT {
+                builder.push(i, 0.0, 1e-3, &data);
+            }
+            for i in 0..$step_count {
+                test::black_box(builder.pull((i + 1) as f64 * 1e-3));
+            }
+        });
+    });
+);
+
+macro_rules! push_pull_interleaved(
+    ($bencher:ident, $builder:ident) => ({
+        let data = vec![1.0; 1000];
+        $bencher.iter(|| {
+            let mut builder = $builder::new(ELEMENT_COUNT, 1e-3, vec![0.0; ELEMENT_COUNT]);
+            for i in 0..STEP_COUNT {
+                let time = i as f64 * 1e-3;
+                builder.push(i % ELEMENT_COUNT, time, 1e-3, &data);
+                test::black_box(builder.step());
+            }
+        });
+    });
+);
+
+#[bench]
+fn pull_long(bencher: &mut Bencher) {
+    pull!(bencher, ProfileBuilder, STEP_COUNT);
+}
+
+#[bench]
+fn pull_short(bencher: &mut Bencher) {
+    pull!(bencher, ProfileBuilder, SHORT_STEP_COUNT);
+}
+
+#[bench]
+fn pull_short_copying(bencher: &mut Bencher) {
+    pull!(bencher, CopyingBuilder, SHORT_STEP_COUNT);
+}
+
+#[bench]
+fn push_pull_interleaved(bencher: &mut Bencher) {
+    push_pull_interleaved!(bencher, ProfileBuilder);
+}
+
+#[bench]
+fn push_pull_interleaved_copying(bencher: &mut Bencher) {
+    push_pull_interleaved!(bencher, CopyingBuilder);
+}
+
+// The baseline: the builder as it was before the data that have not been
+// pulled yet were kept in a ring buffer. On every pull, the data are copied
+// into a new vector.
+struct CopyingBuilder {
+    profile: Profile,
+    fill: Vec<f64>,
+}
+
+impl CopyingBuilder {
+    fn new(element_count: usize, time_step: f64, fill: Vec<f64>) -> CopyingBuilder {
+        CopyingBuilder { profile: Profile::new(element_count, time_step), fill: fill }
+    }
+
+    fn push(&mut self, element_id: usize, time: f64, time_step: f64, data: &[f64]) {
+        let &mut CopyingBuilder { ref mut profile, ref fill } = self;
+        let (t1, t2) = (profile.time, time);
+        let (d1, d2) = (profile.time_step, time_step);
+        let s2 = data.len();
+        if t2 + (s2 as f64) * d2 <= t1 {
+            return;
+        }
+        let s1 = ((t2 - t1 + (s2 as f64) * d2) / d1).ceil() as usize;
+        if s1 > profile.step_count {
+            let more = s1 - profile.step_count;
+            extend(profile, more, fill);
+        }
+        let (mut j1, mut j2) = if t2 < t1 {
+            (0, ((t1 - t2) / d2) as usize)
+        } else {
+            (((t2 - t1) / d1) as usize, 0)
+        };
+        macro_rules! add(
+            ($weight:expr) => (
+                profile.data[j1 * profile.element_count + element_id] += $weight * data[j2];
+            );
+        );
+        while j1 < s1 && j2 < s2 {
+            let l1 = t1 + (j1 as f64) * d1;
+            let l2 = t2 + (j2 as f64) * d2;
+            let r1 = l1 + d1;
+            let r2 = l2 + d2;
+            if l1 < l2 {
+                if r2 < r1 {
+                    add!(1.0);
+                    j2 += 1;
+                } else {
+                    add!((r1 - l2) / d2);
+                    j1 += 1;
+                }
+            } else {
+                if r1 < r2 {
+                    add!(d1 / d2);
+                    j1 += 1;
+                } else {
+                    add!((r2 - l1) / d2);
+                    j2 += 1;
+                }
+            }
+        }
+    }
+
+    fn pull(&mut self, time: f64) -> Profile {
+        let step_count = ((time - self.profile.time) / self.profile.time_step).floor() as usize;
+        let time = (time / self.profile.time_step).floor() * self.profile.time_step;
+        self.split(step_count, time)
+    }
+
+    fn step(&mut self) -> Profile {
+        let time = self.profile.time + self.profile.time_step;
+        self.split(1, time)
+    }
+
+    fn split(&mut self, step_count: usize, time: f64) -> Profile {
+        let &mut CopyingBuilder { ref mut profile, ref fill } = self;
+        if profile.step_count < step_count {
+            let more = step_count - profile.step_count;
+            extend(profile, more, fill);
+        }
+        let mut another = Profile {
+            element_count: profile.element_count,
+            step_count: profile.step_count - step_count,
+            time: time,
+            time_step: profile.time_step,
+            data: profile.data[(step_count * profile.element_count)..].to_vec(),
+        };
+        mem::swap(profile, &mut another);
+        another.step_count = step_count;
+        another.data.truncate(step_count * profile.element_count);
+        another
+    }
+}
+
+fn extend(profile: &mut Profile, step_count: usize, fill: &[f64]) {
+    profile.data.reserve(step_count * profile.element_count);
+    for _ in 0..step_count {
+        profile.data.extend(fill);
+    }
+    profile.step_count += step_count;
+}
@@ -1,18 +1,48 @@
 use sqlite::{Connection, Statement, State};
+use std::fs::File;
+use std::io::{BufWriter, Write};
 use std::mem;
 
 use streamer::{Config, Result};
-use streamer::platform::{self, Platform, Profile};
+use streamer::platform::{Data, Element, Map, Profile, Reliability, Variation};
 use streamer::system::{Event, EventKind, Job};
 
 pub struct Output {
     connection: Connection,
     arrivals: Statement<'static>,
-    profiles: Statement<'static>,
+    throttles: Statement<'static>,
+    probes: Statement<'static>,
+    profiles: Option<Statement<'static>>,
+    maps: Option<Maps>,
+}
+
+/// A writer of temperature maps.
+///
+/// Every `map_decimation`th time step is written either into a table (`map`
+/// set to `table`) or into a binary file (`map` set to `binary` with the file
+/// given by `map_path`). The binary file starts with the number of rows and
+/// columns as 64-bit unsigned integers, and each frame consists of the time
+/// followed by the temperature of the cells as 64-bit floating-point numbers,
+/// all in little endian.
+///
+/// Maps are produced only by the compact thermal model discretized into a
+/// grid, and they cover the die alone: the thermal interface material is
+/// lumped into the cells of the die, and the heat spreader and the heat sink
+/// are single nodes, so there are no other layers to write.
+struct Maps {
+    sink: Sink,
+    decimation: usize,
+    step: usize,
+}
+
+enum Sink {
+    Table(Statement<'static>),
+    Binary(BufWriter<File>, bool),
 }
 
 impl Output {
-    pub fn new(platform: &platform::Thermal, config: &Config) -> Result<Self> {
+    pub fn new(config: &Config, elements: &[Element], variation: Option<&Variation>)
+               -> Result<Self> {
         use sql::prelude::*;
 
         let connection = ok!(Connection::open(path!(@unchecked config,
@@ -27,41 +57,91 @@ impl Output {
             ]).compile())
         ));
         ok!(connection.execute(
-            ok!(create_table("profiles").if_not_exists().columns(&[
+            ok!(create_table("throttles").if_not_exists().columns(&[
                 "time".float().not_null(), "component_id".integer().not_null(),
-                "power".float().not_null(), "temperature".float().not_null(),
+                "speed".float().not_null(),
+            ]).compile())
+        ));
+        ok!(connection.execute(
+            ok!(create_table("probes").if_not_exists().columns(&[
+                "time".float().not_null(), "probe_id".integer().not_null(),
+                "temperature".float().not_null(),
             ]).compile())
         ));
         ok!(connection.execute(ok!(delete_from("arrivals").compile())));
-        ok!(connection.execute(ok!(delete_from("profiles").compile())));
+        ok!(connection.execute(ok!(delete_from("throttles").compile())));
+        ok!(connection.execute(ok!(delete_from("probes").compile())));
+        ok!(connection.execute("DROP TABLE IF EXISTS profiles"));
+        ok!(connection.execute("DROP TABLE IF EXISTS elements"));
+        try!(write_elements(&connection, elements));
+        ok!(connection.execute("DROP TABLE IF EXISTS variation"));
+        ok!(connection.execute("DROP TABLE IF EXISTS material_variation"));
+        if let Some(variation) = variation {
+            try!(write_variation(&connection, variation));
+        }
+        ok!(connection.execute("DROP TABLE IF EXISTS maps"));
+        ok!(connection.execute("DROP TABLE IF EXISTS reliability"));
         let arrivals = {
             let statement = ok!(connection.prepare(
                 ok!(insert_into("arrivals").columns(&["time"]).compile())
             ));
             unsafe { mem::transmute(statement) }
         };
-        let profiles = {
-            let element_count = platform.elements().len();
+        let throttles = {
+            let statement = ok!(connection.prepare(
+                ok!(insert_into("throttles").columns(&["time", "component_id", "speed"]).compile())
+            ));
+            unsafe { mem::transmute(statement) }
+        };
+        let probes = {
             let statement = ok!(connection.prepare(
-                ok!(insert_into("profiles").columns(&[
-                    "time", "component_id", "power", "temperature",
-                ]).batch(element_count).compile())
+                ok!(insert_into("probes").columns(&["time", "probe_id", "temperature"])
+                                         .compile())
             ));
             unsafe { mem::transmute(statement) }
         };
-        Ok(Output { connection: connection, arrivals: arrivals, profiles: profiles })
+        let maps = match config.get::<String>("map") {
+            Some(format) => Some(try!(Maps::new(&connection, &format, config))),
+            _ => None,
+        };
+        Ok(Output {
+            connection: connection,
+            arrivals: arrivals,
+            throttles: throttles,
+            probes: probes,
+            profiles: None,
+            maps: maps,
+        })
     }
 
-    pub fn next(&mut self, event: &Event, profiles: &(Profile, Profile)) -> Result<()> {
+    pub fn next(&mut self, event: &Event, data: &Data) -> Result<()> {
         ok!(self.connection.execute("BEGIN TRANSACTION"));
-        if let &EventKind::Arrive(ref job) = &event.kind {
-            ok!(self.write_arrival(job));
+        match &event.kind {
+            &EventKind::Arrive(ref job) => ok!(self.write_arrival(job)),
+            &EventKind::Throttle(element, speed) => {
+                ok!(self.write_throttle(event.time, element, speed))
+            },
+            &EventKind::Unthrottle(element) => ok!(self.write_throttle(event.time, element, 1.0)),
+            _ => {},
+        }
+        ok!(self.write_profiles(&data.channels()));
+        if let Some(ref probes) = data.probes {
+            ok!(self.write_probes(probes));
+        }
+        if let (Some(maps), Some(map)) = (self.maps.as_mut(), data.map.as_ref()) {
+            ok!(maps.write(map));
         }
-        ok!(self.write_profiles(profiles));
         ok!(self.connection.execute("END TRANSACTION"));
         Ok(())
     }
 
+    pub fn finish(&mut self, reliability: Option<&Reliability>) -> Result<()> {
+        if let Some(reliability) = reliability {
+            try!(write_reliability(&self.connection, reliability));
+        }
+        Ok(())
+    }
+
     fn write_arrival(&mut self, job: &Job) -> Result<()> {
         let statement = &mut self.arrivals;
         ok!(statement.reset());
@@ -72,10 +152,42 @@ impl Output {
         Ok(())
     }
 
-    fn write_profiles(&mut self, profiles: &(Profile, Profile)) -> Result<()> {
-        let &Profile { element_count, step_count, time, time_step, data: ref power } = &profiles.0;
-        let &Profile { data: ref temperature, .. } = &profiles.1;
-        let statement = &mut self.profiles;
+    fn write_throttle(&mut self, time: f64, element: usize, speed: f64) -> Result<()> {
+        let statement = &mut self.throttles;
+        ok!(statement.reset());
+        ok!(statement.bind(1, time));
+        ok!(statement.bind(2, element as i64));
+        ok!(statement.bind(3, speed));
+        if State::Done != ok!(statement.next()) {
+            raise!("failed to write into the database");
+        }
+        Ok(())
+    }
+
+    fn write_probes(&mut self, probes: &Profile) -> Result<()> {
+        let &Profile { element_count, step_count, time, time_step, .. } = probes;
+        let statement = &mut self.probes;
+        for i in 0..step_count {
+            let time = time + (i as f64) * time_step;
+            for j in 0..element_count {
+                ok!(statement.reset());
+                ok!(statement.bind(1, time));
+                ok!(statement.bind(2, j as i64));
+                ok!(statement.bind(3, probes[i * element_count + j]));
+                if State::Done != ok!(statement.next()) {
+                    raise!("failed to write into the database");
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn write_profiles(&mut self, channels: &[(&str, &Profile)]) -> Result<()> {
+        let &Profile { element_count, step_count, time, time_step, .. } = channels[0].1;
+        if self.profiles.is_none() {
+            self.profiles = Some(try!(self.prepare_profiles(element_count, channels)));
+        }
+        let statement = self.profiles.as_mut().unwrap();
         for i in 0..step_count {
             let time = time + (i as f64) * time_step;
             ok!(statement.reset());
@@ -83,9 +195,11 @@ impl Output {
             for j in 0..element_count {
                 ok!(statement.bind(k + 1, time));
                 ok!(statement.bind(k + 2, j as i64));
-                ok!(statement.bind(k + 3, power[i * element_count + j]));
-                ok!(statement.bind(k + 4, temperature[i * element_count + j]));
-                k += 4;
+                k += 2;
+                for &(_, profile) in channels {
+                    ok!(statement.bind(k + 1, profile[i * element_count + j]));
+                    k += 1;
+                }
             }
             if State::Done != ok!(statement.next()) {
                 raise!("failed to write into the database");
@@ -93,4 +207,219 @@ impl Output {
         }
         Ok(())
     }
+
+    fn prepare_profiles(&self, element_count: usize, channels: &[(&str, &Profile)])
+                        -> Result<Statement<'static>> {
+        use sql::prelude::*;
+
+        let mut columns = vec!["time".float().not_null(), "component_id".integer().not_null()];
+        for &(name, _) in channels {
+            columns.push(name.float().not_null());
+        }
+        ok!(self.connection.execute(
+            ok!(create_table("profiles").columns(&columns).compile())
+        ));
+        let mut columns = vec!["time", "component_id"];
+        columns.extend(channels.iter().map(|&(name, _)| name));
+        let statement = ok!(self.connection.prepare(
+            ok!(insert_into("profiles").columns(&columns).batch(element_count).compile())
+        ));
+        Ok(unsafe { mem::transmute(statement) })
+    }
+}
+
+impl Maps {
+    fn new(connection: &Connection, format: &str, config: &Config) -> Result<Maps> {
+        use sql::prelude::*;
+
+        let decimation = *config.get::<i64>("map_decimation").unwrap_or(&1);
+        if decimation <= 0 {
+            raise!("the decimation of temperature maps should be positive");
+        }
+        let sink = match format {
+            "table" => {
+                ok!(connection.execute(
+                    ok!(create_table("maps").columns(&[
+                        "time".float().not_null(), "row_id".integer().not_null(),
+                        "column_id".integer().not_null(), "temperature".float().not_null(),
+                    ]).compile())
+                ));
+                let statement = ok!(connection.prepare(
+                    ok!(insert_into("maps").columns(&[
+                        "time", "row_id", "column_id", "temperature",
+                    ]).compile())
+                ));
+                Sink::Table(unsafe { mem::transmute(statement) })
+            },
+            "binary" => {
+                let path = path!(@unchecked config["map_path"],
+                                 "a file for temperature maps is required");
+                Sink::Binary(BufWriter::new(ok!(File::create(path))), false)
+            },
+            _ => raise!("found an unknown format of temperature maps ({:?})", format),
+        };
+        Ok(Maps { sink: sink, decimation: decimation as usize, step: 0 })
+    }
+
+    fn write(&mut self, map: &Map) -> Result<()> {
+        let &Map { rows, columns, ref temperature } = map;
+        let cell_count = rows * columns;
+        for i in 0..temperature.step_count {
+            let step = self.step;
+            self.step += 1;
+            if step % self.decimation != 0 {
+                continue;
+            }
+            let time = temperature.time + (i as f64) * temperature.time_step;
+            let cells = &temperature[(i * cell_count)..((i + 1) * cell_count)];
+            match self.sink {
+                Sink::Table(ref mut statement) => {
+                    for (k, &value) in cells.iter().enumerate() {
+                        ok!(statement.reset());
+                        ok!(statement.bind(1, time));
+                        ok!(statement.bind(2, (k / columns) as i64));
+                        ok!(statement.bind(3, (k % columns) as i64));
+                        ok!(statement.bind(4, value));
+                        if State::Done != ok!(statement.next()) {
+                            raise!("failed to write into the database");
+                        }
+                    }
+                },
+                Sink::Binary(ref mut writer, ref mut started) => {
+                    if !*started {
+                        ok!(write_bits(writer, rows as u64));
+                        ok!(write_bits(writer, columns as u64));
+                        *started = true;
+                    }
+                    ok!(write_bits(writer, time.to_bits()));
+                    for &value in cells {
+                        ok!(write_bits(writer, value.to_bits()));
+                    }
+                },
+            }
+        }
+        if let Sink::Binary(ref mut writer, _) = self.sink {
+            ok!(writer.flush());
+        }
+        Ok(())
+    }
+}
+
+fn write_elements(connection: &Connection, elements: &[Element]) -> Result<()> {
+    use sql::prelude::*;
+
+    ok!(connection.execute(
+        ok!(create_table("elements").columns(&[
+            "component_id".integer().not_null(), "name".string().not_null(),
+            "kind".string().not_null(), "die".integer().not_null(),
+            "area".float().not_null(), "x".float(), "y".float(),
+            "width".float(), "height".float(),
+        ]).compile())
+    ));
+    let mut statement = ok!(connection.prepare(
+        ok!(insert_into("elements").columns(&[
+            "component_id", "name", "kind", "die", "area", "x", "y", "width", "height",
+        ]).compile())
+    ));
+    for element in elements {
+        ok!(statement.reset());
+        ok!(statement.bind(1, element.id as i64));
+        ok!(statement.bind(2, &element.name[..]));
+        ok!(statement.bind(3, element.kind.name()));
+        ok!(statement.bind(4, element.die as i64));
+        ok!(statement.bind(5, element.area));
+        // SQLite stores NaN as NULL.
+        let bounds = match element.bounds {
+            Some(bounds) => [bounds.left, bounds.bottom, bounds.width, bounds.height],
+            _ => [::std::f64::NAN; 4],
+        };
+        for (i, &value) in bounds.iter().enumerate() {
+            ok!(statement.bind(6 + i, value));
+        }
+        if State::Done != ok!(statement.next()) {
+            raise!("failed to write into the database");
+        }
+    }
+    Ok(())
+}
+
+fn write_variation(connection: &Connection, variation: &Variation) -> Result<()> {
+    use sql::prelude::*;
+
+    ok!(connection.execute(
+        ok!(create_table("variation").columns(&[
+            "component_id".integer().not_null(), "leakage_scale".float().not_null(),
+        ]).compile())
+    ));
+    let mut statement = ok!(connection.prepare(
+        ok!(insert_into("variation").columns(&["component_id", "leakage_scale"]).compile())
+    ));
+    for (i, &scale) in variation.leakage.iter().enumerate() {
+        ok!(statement.reset());
+        ok!(statement.bind(1, i as i64));
+        ok!(statement.bind(2, scale));
+        if State::Done != ok!(statement.next()) {
+            raise!("failed to write into the database");
+        }
+    }
+    let conductivity = match variation.conductivity {
+        Some(conductivity) => conductivity,
+        _ => return Ok(()),
+    };
+    ok!(connection.execute(
+        ok!(create_table("material_variation").columns(&[
+            "layer".string().not_null(), "conductivity_scale".float().not_null(),
+        ]).compile())
+    ));
+    let mut statement = ok!(connection.prepare(
+        ok!(insert_into("material_variation").columns(&["layer", "conductivity_scale"])
+                                             .compile())
+    ));
+    let layers = ["die", "interface", "spreader", "sink"];
+    for (&layer, &scale) in layers.iter().zip(conductivity.iter()) {
+        ok!(statement.reset());
+        ok!(statement.bind(1, layer));
+        ok!(statement.bind(2, scale));
+        if State::Done != ok!(statement.next()) {
+            raise!("failed to write into the database");
+        }
+    }
+    Ok(())
+}
+
+fn write_bits<W: Write>(writer: &mut W, bits: u64) -> ::std::io::Result<()> {
+    let mut bytes = [0u8; 8];
+    for (i, byte) in bytes.iter_mut().enumerate() {
+        *byte = (bits >> (8 * i)) as u8;
+    }
+    writer.write_all(&bytes)
+}
+
+fn write_reliability(connection: &Connection, reliability: &Reliability) -> Result<()> {
+    use sql::prelude::*;
+
+    ok!(connection.execute(
+        ok!(create_table("reliability").columns(&[
+            "component_id".integer().not_null(), "electromigration".float().not_null(),
+            "tddb".float().not_null(), "cycling".float().not_null(), "mttf".float(),
+        ]).compile())
+    ));
+    let mut statement = ok!(connection.prepare(
+        ok!(insert_into("reliability").columns(&[
+            "component_id", "electromigration", "tddb", "cycling", "mttf",
+        ]).compile())
+    ));
+    for (i, (wear, mttf)) in reliability.wears().iter().zip(reliability.mttf()).enumerate() {
+        ok!(statement.reset());
+        ok!(statement.bind(1, i as i64));
+        ok!(statement.bind(2, wear.electromigration));
+        ok!(statement.bind(3, wear.tddb));
+        ok!(statement.bind(4, wear.cycling));
+        // SQLite stores NaN as NULL.
+        ok!(statement.bind(5, mttf.unwrap_or(::std::f64::NAN)));
+        if State::Done != ok!(statement.next()) {
+            raise!("failed to write into the database");
+        }
+    }
+    Ok(())
 }
@@ -9,7 +9,8 @@ extern crate term;
 
 use configuration::format::TOML;
 use log::LogLevel;
-use streamer::{Result, platform, schedule, traffic, workload};
+use streamer::{Config, Result, platform, schedule, traffic, workload};
+use streamer::platform::{Data, ElementClassifier, Platform};
 use streamer::system::{self, Event};
 
 mod logger;
@@ -18,10 +19,10 @@ mod output;
 use logger::Logger;
 use output::Output;
 
-type System = system::System<traffic::Fractal,
-                             workload::Random,
-                             platform::Thermal,
-                             schedule::Impartial>;
+type System<P> = system::System<traffic::Fractal,
+                                workload::Random,
+                                P,
+                                schedule::Impartial>;
 
 const USAGE: &'static str = "
 Usage: streamer [options]
@@ -59,18 +60,37 @@ fn start() -> Result<()> {
     }
     let config = ok!(TOML::open(some!(arguments.get::<String>("config"),
                                       "a configuration file is required")));
-    macro_rules! branch(($name:expr) => (config.branch($name).as_ref().unwrap_or(&config)));
+    let branch = config.branch("platform");
+    let branch = branch.as_ref().unwrap_or(&config);
+    let source = streamer::source(&config);
+    let classifier = try!(platform::ElementClassifier::new(&config));
+    match branch.get::<String>("type") {
+        Some(kind) if kind == "power" => {
+            run(&config, &classifier, try!(platform::Power::new(branch, &classifier)))
+        },
+        Some(kind) if kind == "thermal" => {
+            run(&config, &classifier, try!(platform::Thermal::new(branch, &classifier, source)))
+        },
+        Some(kind) => raise!("found an unknown platform type ({:?})", kind),
+        _ => run(&config, &classifier, try!(platform::Thermal::new(branch, &classifier, source))),
+    }
+}
+
+fn run<P: Platform<Data = Data>>(config: &Config, classifier: &ElementClassifier, platform: P)
+                                 -> Result<()> {
+    macro_rules! branch(($name:expr) => (config.branch($name).as_ref().unwrap_or(config)));
     let mut system = {
-        let source = streamer::source(&config);
+        let source = streamer::source(config);
         let traffic = try!(traffic::Fractal::new(branch!("traffic"), source.clone()));
-        let workload = try!(workload::Random::new(branch!("workload"), source.clone()));
-        let platform = try!(platform::Thermal::new(branch!("platform")));
+        let workload = try!(workload::Random::new(branch!("workload"), classifier,
+                                                  source.clone()));
         let schedule = try!(schedule::Impartial::new(branch!("schedule"), &platform, source));
         try!(System::new(traffic, workload, platform, schedule))
     };
     let time_span = *some!(config.get::<f64>("output.time_span"), "a time span is required");
     let mut output = if config.get::<String>("output.path").is_some() {
-        Some(try!(Output::new(system.platform(), branch!("output"))))
+        let platform = system.platform();
+        Some(try!(Output::new(branch!("output"), platform.elements(), platform.variation())))
     } else {
         None
     };
@@ -84,17 +104,30 @@ fn start() -> Result<()> {
             try!(output.next(&event, &data));
         }
     }
+    if let Some(ref mut output) = output {
+        try!(output.finish(system.platform().reliability()));
+    }
     info!(target: "Streamer", "Well done.");
     Ok(())
 }
 
-fn display(system: &System, event: &Event) {
+fn display<P: Platform>(system: &System<P>, event: &Event) {
     use streamer::system::EventKind;
 
     let (job, kind) = match &event.kind {
         &EventKind::Arrive(ref job) => (job, "arrive"),
         &EventKind::Start(ref job, _) => (job, "start"),
         &EventKind::Finish(ref job, _) => (job, "finish"),
+        &EventKind::Throttle(element, speed) => {
+            info!(target: "Streamer", "{:10.2} s | {:6} | element {:<3} at {:4.2} speed",
+                  event.time, "throttle", element, speed);
+            return;
+        },
+        &EventKind::Unthrottle(element) => {
+            info!(target: "Streamer", "{:10.2} s | {:6} | element {:<3}",
+                  event.time, "release", element);
+            return;
+        },
     };
     info!(target: "Streamer",
           "{:10.2} s | {:6} | # {:<5} ( {:15} | {:2} components | {:6.2} s ) {:2} queued",
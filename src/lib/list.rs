@@ -0,0 +1,65 @@
+//! Reading lists from configurations.
+
+use std::any::Any;
+use std::str::FromStr;
+
+use {Config, Result};
+
+/// Read a list of values.
+///
+/// Since arrays of values cannot be stored in a configuration, a list is given
+/// either as a single value, as a comma-separated string, or as an array of
+/// tables each having the value under `key`. The function returns `None` if
+/// the list is absent.
+pub fn read<T>(config: &Config, name: &str, key: &str) -> Result<Option<Vec<T>>>
+    where T: Any + Clone + FromStr
+{
+    if let Some(ref configs) = config.forest(name) {
+        let mut values = Vec::with_capacity(configs.len());
+        for config in configs {
+            values.push(some!(config.get::<T>(key), "expected {:?} in each entry of {:?}",
+                              key, name).clone());
+        }
+        return Ok(Some(values));
+    }
+    if let Some(line) = config.get::<String>(name) {
+        let mut values = vec![];
+        for chunk in line.split(',').map(|chunk| chunk.trim()).filter(|chunk| !chunk.is_empty()) {
+            match chunk.parse::<T>() {
+                Ok(value) => values.push(value),
+                _ => raise!("failed to parse {:?} in {:?}", chunk, name),
+            }
+        }
+        return Ok(Some(values));
+    }
+    Ok(config.get::<T>(name).map(|value| vec![value.clone()]))
+}
+
+#[cfg(test)]
+mod tests {
+    use configuration::format::TOML;
+
+    #[test]
+    fn read() {
+        let config = TOML::parse(r#"
+            one = 1
+            many = "1, 2,3"
+            names = "Core0, core1"
+
+            [[tables]]
+            id = 4
+
+            [[tables]]
+            id = 2
+        "#).unwrap();
+
+        assert_eq!(super::read::<i64>(&config, "one", "id").unwrap(), Some(vec![1]));
+        assert_eq!(super::read::<i64>(&config, "many", "id").unwrap(), Some(vec![1, 2, 3]));
+        assert_eq!(super::read::<String>(&config, "names", "name").unwrap(),
+                   Some(vec!["Core0".to_string(), "core1".to_string()]));
+        assert_eq!(super::read::<i64>(&config, "tables", "id").unwrap(), Some(vec![4, 2]));
+        assert_eq!(super::read::<i64>(&config, "none", "id").unwrap(), None);
+        assert!(super::read::<i64>(&config, "names", "id").is_err());
+        assert!(super::read::<String>(&config, "tables", "name").is_err());
+    }
+}
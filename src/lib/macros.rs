@@ -66,10 +66,15 @@ macro_rules! order {
 }
 
 /// Fetch a path from a configuration or raise an error.
+///
+/// The path is read from `path` unless another key is given in brackets.
 #[macro_export]
 macro_rules! path(
-    (@unchecked $config:ident, $($argument:tt)+) => ({
-        let path = some!($config.get::<String>("path"), $($argument)+);
+    (@unchecked $config:ident, $($argument:tt)+) => (
+        path!(@unchecked $config["path"], $($argument)+)
+    );
+    (@unchecked $config:ident[$key:expr], $($argument:tt)+) => ({
+        let path = some!($config.get::<String>($key), $($argument)+);
         let mut path = ::std::path::PathBuf::from(path);
         if path.is_relative() {
             if let Some(root) = $config.get::<String>("root") {
@@ -78,8 +83,9 @@ macro_rules! path(
         }
         path
     });
-    ($config:ident, $($argument:tt)+) => ({
-        let path = path!(@unchecked $config, $($argument)+);
+    ($config:ident, $($argument:tt)+) => (path!($config["path"], $($argument)+));
+    ($config:ident[$key:expr], $($argument:tt)+) => ({
+        let path = path!(@unchecked $config[$key], $($argument)+);
         if ::std::fs::metadata(&path).is_err() {
             raise!("the file {:?} does not exist", &path);
         }
@@ -0,0 +1,237 @@
+use matrix::format::{Compressed, Conventional, Diagonal};
+use temperature::Circuit;
+
+use {Config, Result};
+use platform::Floorplan;
+
+/// A compact block-level thermal model.
+///
+/// The model has one node per floorplan block, one node for the heat spreader,
+/// and one node for the heat sink. The blocks are connected laterally through
+/// shared edges and vertically to the spreader, the spreader to the sink, and
+/// the sink to the ambience.
+#[derive(Clone, Debug)]
+pub struct Compact {
+    /// The die.
+    pub die: Layer,
+    /// The thermal interface material between the die and the spreader.
+    pub interface: Layer,
+    /// The heat spreader.
+    pub spreader: Layer,
+    /// The heat sink.
+    pub sink: Layer,
+    /// The heat transfer coefficient between the sink and the ambience.
+    pub convection: f64,
+}
+
+/// A layer of material.
+#[derive(Clone, Copy, Debug)]
+pub struct Layer {
+    /// The side of the square footprint (zero if given by the floorplan).
+    pub side: f64,
+    /// The thickness.
+    pub thickness: f64,
+    /// The thermal conductivity.
+    pub conductivity: f64,
+    /// The volumetric heat capacity.
+    pub capacity: f64,
+}
+
+impl Compact {
+    /// Create a model.
+    pub fn new(config: &Config) -> Result<Compact> {
+        macro_rules! get(
+            ($name:expr, $default:expr) => (*config.get::<f64>($name).unwrap_or(&$default));
+        );
+        macro_rules! layer(
+            ($name:expr, $side:expr, $thickness:expr, $conductivity:expr, $capacity:expr) => ({
+                let layer = Layer {
+                    side: get!(concat!($name, "_side"), $side),
+                    thickness: get!(concat!($name, "_thickness"), $thickness),
+                    conductivity: get!(concat!($name, "_conductivity"), $conductivity),
+                    capacity: get!(concat!($name, "_capacity"), $capacity),
+                };
+                if layer.side < 0.0 || layer.thickness <= 0.0 {
+                    raise!("the dimensions of the {} should be positive", $name);
+                }
+                if layer.conductivity <= 0.0 || layer.capacity <= 0.0 {
+                    raise!("the material of the {} should have positive properties", $name);
+                }
+                layer
+            });
+        );
+        let model = Compact {
+            die: layer!("die", 0.0, 150e-6, 100.0, 1.75e6),
+            interface: layer!("interface", 0.0, 20e-6, 4.0, 4e6),
+            spreader: layer!("spreader", 0.03, 1e-3, 400.0, 3.55e6),
+            sink: layer!("sink", 0.06, 6.9e-3, 400.0, 3.55e6),
+            convection: get!("heat_transfer_coefficient", 1e5),
+        };
+        if model.spreader.side <= 0.0 || model.sink.side <= 0.0 || model.convection <= 0.0 {
+            raise!("the spreader, the sink, and the convection should be positive");
+        }
+        Ok(model)
+    }
+
+    /// Construct a thermal circuit for a floorplan.
+    #[inline]
+    pub fn circuit(&self, floorplan: &Floorplan) -> Circuit {
+        self.assemble(floorplan, false)
+    }
+
+    /// Construct a thermal circuit for a floorplan with the heat sink exposed
+    /// as an extra unit following the blocks.
+    ///
+    /// The power of the extra unit is injected into the sink, and its
+    /// temperature is the one of the sink.
+    #[inline]
+    pub fn circuit_with_sink(&self, floorplan: &Floorplan) -> Circuit {
+        self.assemble(floorplan, true)
+    }
+
+    /// Compute the thermal conductance between the heat sink and the ambience.
+    #[inline]
+    pub fn sink_conductance(&self) -> f64 {
+        self.convection * self.sink.side * self.sink.side
+    }
+
+    /// Compute the thermal capacitance vector and the thermal conductance
+    /// matrix (a dense symmetric matrix) for a floorplan.
+    pub fn network(&self, floorplan: &Floorplan) -> (Vec<f64>, Vec<f64>) {
+        let &Compact { ref die, ref interface, ref spreader, ref sink, convection } = self;
+        let unit_count = floorplan.blocks.len();
+        let node_count = unit_count + 2;
+        let (spreader_id, sink_id) = (unit_count, unit_count + 1);
+
+        let mut capacitance = vec![0.0; node_count];
+        let mut conductance = vec![0.0; node_count * node_count];
+        macro_rules! connect(
+            ($i:expr, $j:expr, $value:expr) => ({
+                let (i, j, value) = ($i, $j, $value);
+                conductance[i * node_count + i] += value;
+                conductance[j * node_count + j] += value;
+                conductance[i * node_count + j] -= value;
+                conductance[j * node_count + i] -= value;
+            });
+        );
+
+        let blocks = &floorplan.blocks;
+        for (i, block) in blocks.iter().enumerate() {
+            let area = block.area();
+            capacitance[i] = (die.capacity * die.thickness +
+                              interface.capacity * interface.thickness) * area;
+            let resistance = die.thickness / (2.0 * die.conductivity) +
+                             interface.thickness / interface.conductivity +
+                             spreader.thickness / (2.0 * spreader.conductivity);
+            connect!(i, spreader_id, area / resistance);
+            for j in (i + 1)..unit_count {
+                if let Some((length, distance)) = block.bounds().contact(&blocks[j].bounds()) {
+                    connect!(i, j, die.conductivity * die.thickness * length / distance);
+                }
+            }
+        }
+
+        let area = spreader.side * spreader.side;
+        capacitance[spreader_id] = spreader.capacity * spreader.thickness * area;
+        connect!(spreader_id, sink_id,
+                 1.0 / (spreader.thickness / (2.0 * spreader.conductivity * area) +
+                        sink.thickness / (2.0 * sink.conductivity * sink.side * sink.side)));
+
+        let area = sink.side * sink.side;
+        capacitance[sink_id] = sink.capacity * sink.thickness * area;
+        conductance[sink_id * node_count + sink_id] += convection * area;
+
+        (capacitance, conductance)
+    }
+
+    fn assemble(&self, floorplan: &Floorplan, exposed: bool) -> Circuit {
+        let unit_count = floorplan.blocks.len();
+        let node_count = unit_count + 2;
+        let (capacitance, conductance) = self.network(floorplan);
+        let (mut distribution, mut aggregation) = if exposed {
+            let mut distribution = Conventional::new((node_count, unit_count + 1));
+            let mut aggregation = Conventional::new((unit_count + 1, node_count));
+            distribution[(unit_count + 1, unit_count)] = 1.0;
+            aggregation[(unit_count, unit_count + 1)] = 1.0;
+            (distribution, aggregation)
+        } else {
+            (Conventional::new((node_count, unit_count)),
+             Conventional::new((unit_count, node_count)))
+        };
+        for i in 0..unit_count {
+            distribution[(i, i)] = 1.0;
+            aggregation[(i, i)] = 1.0;
+        }
+        Circuit {
+            capacitance: Diagonal::from_vec(node_count, capacitance),
+            conductance: Compressed::from(&Conventional::from_vec((node_count, node_count),
+                                                                  conductance)),
+            distribution: Compressed::from(&distribution),
+            aggregation: Compressed::from(&aggregation),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use assert;
+    use configuration::format::TOML;
+    use temperature::{Circuit, Config, Simulator};
+    use temperature::circuit::ThreeDICE;
+
+    use platform::Floorplan;
+    use super::Compact;
+
+    #[test]
+    fn network() {
+        let floorplan = Floorplan::open("tests/fixtures/004_hotspot.flp").unwrap();
+        let config = TOML::open("tests/fixtures/streamer.toml").unwrap();
+        let model = Compact::new(&config).unwrap();
+        let (capacitance, conductance) = model.network(&floorplan);
+        let n = 5 + 2;
+
+        assert_eq!(capacitance.len(), n);
+        assert_eq!(conductance.len(), n * n);
+        for i in 0..n {
+            for j in 0..n {
+                assert_eq!(conductance[i * n + j], conductance[j * n + i]);
+            }
+        }
+
+        let lateral = 100.0 * 150e-6 * 7950e-6 / 3976e-6;
+        assert::close(&[-conductance[1], -conductance[n + 2]], &[lateral, lateral], 1e-12);
+        assert_eq!(conductance[2], 0.0);
+        assert::close(&[-conductance[4]],
+                      &[100.0 * 150e-6 * 3976e-6 / ((7950e-6 + 3894e-6) / 2.0)], 1e-12);
+
+        let sums = (0..n).map(|i| (0..n).map(|j| conductance[i * n + j]).sum::<f64>())
+                         .collect::<Vec<_>>();
+        assert::close(&sums, &[0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 1e5 * 0.06 * 0.06], 1e-9);
+    }
+
+    // The compact model and 3D-ICE describe the same stack (004.stk versus the
+    // defaults of the compact model). With the cores dissipating 10 W each,
+    // the temperature rises by about 2.5 K, and the steady-state temperatures
+    // of the two models are expected to agree within 0.5 K.
+    #[test]
+    fn threed_ice() {
+        let (unit_count, step_count) = (5, 1000);
+        let power = [10.0, 10.0, 10.0, 10.0, 2.0];
+        let steady = |circuit: Circuit| {
+            let config = Config { ambience: 318.15, time_step: 1e-2 };
+            let mut simulator = Simulator::new(circuit, config).unwrap();
+            let power = power.iter().cycle().take(step_count * unit_count).cloned()
+                             .collect::<Vec<_>>();
+            let mut temperature = vec![0.0; step_count * unit_count];
+            simulator.next(&power, &mut temperature);
+            temperature[((step_count - 1) * unit_count)..].to_vec()
+        };
+
+        let floorplan = Floorplan::open("tests/fixtures/004_hotspot.flp").unwrap();
+        let config = TOML::open("tests/fixtures/streamer.toml").unwrap();
+        let compact = steady(Compact::new(&config).unwrap().circuit(&floorplan));
+        let threed_ice = steady(ThreeDICE::new("tests/fixtures/004.stk").unwrap());
+        assert!(compact.iter().take(4).all(|&temperature| temperature > 318.15 + 1.0));
+        assert::close(&compact, &threed_ice, 0.5);
+    }
+}
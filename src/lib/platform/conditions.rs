@@ -0,0 +1,144 @@
+use std::path::Path;
+
+use {Config, Result};
+
+/// A time series of cooling conditions.
+///
+/// The ambient temperature and the heat transfer coefficient between the
+/// heat sink and the ambience are interpolated linearly between the given
+/// points in time and held constant outside them. A quantity that is not
+/// given at a point takes its nominal value there.
+#[derive(Clone, Debug)]
+pub struct Conditions {
+    time: Vec<f64>,
+    ambience: Vec<f64>,
+    convection: Vec<f64>,
+}
+
+impl Conditions {
+    /// Create a time series given the nominal ambient temperature and heat
+    /// transfer coefficient.
+    ///
+    /// The series is taken from the `points` array of tables with `time`,
+    /// `ambience`, and `heat_transfer_coefficient` keys if present and from a
+    /// `conditions` table with the same columns in the database given by
+    /// `database` otherwise.
+    pub fn new(config: &Config, ambience: f64, convection: f64) -> Result<Conditions> {
+        let (time, ambience, convection) = match config.forest("points") {
+            Some(ref configs) => {
+                let mut series = (vec![], vec![], vec![]);
+                for config in configs {
+                    series.0.push(*some!(config.get::<f64>("time"),
+                                         "the time of a point of conditions is required"));
+                    series.1.push(*config.get::<f64>("ambience").unwrap_or(&ambience));
+                    series.2.push(*config.get::<f64>("heat_transfer_coefficient")
+                                         .unwrap_or(&convection));
+                }
+                series
+            },
+            _ => try!(read_conditions(&path!(config["database"],
+                                             "a time series of conditions is required"))),
+        };
+        if time.is_empty() {
+            raise!("the time series of conditions should not be empty");
+        }
+        if time.windows(2).any(|pair| pair[0] >= pair[1]) {
+            raise!("the time points of conditions should be ascending");
+        }
+        if convection.iter().any(|&value| value <= 0.0) {
+            raise!("the heat transfer coefficient should be positive");
+        }
+        Ok(Conditions { time: time, ambience: ambience, convection: convection })
+    }
+
+    /// Return the ambient temperature and the heat transfer coefficient at a
+    /// point in time.
+    pub fn at(&self, time: f64) -> (f64, f64) {
+        let n = self.time.len();
+        if time <= self.time[0] {
+            return (self.ambience[0], self.convection[0]);
+        }
+        if time >= self.time[n - 1] {
+            return (self.ambience[n - 1], self.convection[n - 1]);
+        }
+        let i = self.time.iter().position(|&x| x > time).unwrap();
+        let w = (time - self.time[i - 1]) / (self.time[i] - self.time[i - 1]);
+        ((1.0 - w) * self.ambience[i - 1] + w * self.ambience[i],
+         (1.0 - w) * self.convection[i - 1] + w * self.convection[i])
+    }
+}
+
+fn read_conditions(path: &Path) -> Result<(Vec<f64>, Vec<f64>, Vec<f64>)> {
+    use sql::prelude::*;
+    use sqlite::Connection;
+
+    let backend = ok!(Connection::open(path));
+    let (mut time, mut ambience, mut convection) = (vec![], vec![], vec![]);
+    let statement = select_from("conditions").columns(&[
+        "time", "ambience", "heat_transfer_coefficient",
+    ]).order_by(column("time").ascend());
+    let mut cursor = ok!(backend.prepare(ok!(statement.compile()))).cursor();
+    while let Some(row) = ok!(cursor.next()) {
+        match (row[0].as_float(), row[1].as_float(), row[2].as_float()) {
+            (Some(one), Some(two), Some(three)) => {
+                time.push(one);
+                ambience.push(two);
+                convection.push(three);
+            },
+            _ => raise!("found a malformed row in the conditions table"),
+        }
+    }
+    Ok((time, ambience, convection))
+}
+
+#[cfg(test)]
+mod tests {
+    use assert;
+    use configuration::format::TOML;
+    use super::Conditions;
+
+    #[test]
+    fn at() {
+        let conditions = Conditions {
+            time: vec![0.0, 10.0, 20.0],
+            ambience: vec![300.0, 310.0, 305.0],
+            convection: vec![1e5, 1e5, 2e5],
+        };
+        let values = [-1.0, 5.0, 15.0, 25.0].iter().map(|&time| conditions.at(time))
+                                              .collect::<Vec<_>>();
+        assert::close(&values.iter().map(|value| value.0).collect::<Vec<_>>(),
+                      &[300.0, 305.0, 307.5, 305.0], 1e-12);
+        assert::close(&values.iter().map(|value| value.1).collect::<Vec<_>>(),
+                      &[1e5, 1e5, 1.5e5, 2e5], 1e-6);
+    }
+
+    #[test]
+    fn new() {
+        let config = TOML::parse(r#"
+            path = "tests/fixtures/004.stk"
+
+            [conditions]
+
+            [[conditions.points]]
+            time = 0.0
+            ambience = 300.0
+
+            [[conditions.points]]
+            time = 10.0
+            heat_transfer_coefficient = 2e5
+        "#).unwrap();
+        let conditions = Conditions::new(&config.branch("conditions").unwrap(), 318.15, 1e5)
+                                    .unwrap();
+        assert_eq!(conditions.time, vec![0.0, 10.0]);
+        assert_eq!(conditions.ambience, vec![300.0, 318.15]);
+        assert_eq!(conditions.convection, vec![1e5, 2e5]);
+
+        let config = TOML::parse(r#"
+            path = "tests/fixtures/004.stk"
+
+            [conditions]
+            ambience = 300.0
+        "#).unwrap();
+        assert!(Conditions::new(&config.branch("conditions").unwrap(), 318.15, 1e5).is_err());
+    }
+}
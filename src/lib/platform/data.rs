@@ -0,0 +1,72 @@
+use platform::Profile;
+
+/// The data produced by a platform.
+pub struct Data {
+    /// The power consumption.
+    pub power: Profile,
+    /// The states of the processing elements (see `ElementState`).
+    pub state: Profile,
+    /// The temperature if simulated.
+    pub temperature: Option<Profile>,
+    /// The temperature as seen by sensors if modeled.
+    pub sensed: Option<Profile>,
+    /// The peak temperature if a thermal grid is modeled.
+    pub peak: Option<Profile>,
+    /// The temperature at the probes if any (one column per probe).
+    pub probes: Option<Profile>,
+    /// The temperature map if a thermal grid is modeled.
+    pub map: Option<Map>,
+    /// The damage accumulated due to aging if requested.
+    pub damage: Option<Profile>,
+    /// The supply current if power delivery is modeled.
+    pub current: Option<Profile>,
+    /// The supply voltage droop if power delivery is modeled.
+    pub droop: Option<Profile>,
+    /// The dynamic metrics of workloads, such as performance counters, along
+    /// with their names.
+    pub metrics: Vec<(String, Profile)>,
+}
+
+/// A temperature map.
+///
+/// The map is a profile with one column per cell of a grid. The cells are
+/// numbered row by row starting from the bottom-left corner of the die. Only
+/// the die is mapped; the other layers of the compact thermal model are not
+/// discretized.
+pub struct Map {
+    /// The number of rows.
+    pub rows: usize,
+    /// The number of columns.
+    pub columns: usize,
+    /// The temperature of the cells.
+    pub temperature: Profile,
+}
+
+impl Data {
+    /// Return the available per-element profiles along with their names.
+    pub fn channels(&self) -> Vec<(&str, &Profile)> {
+        let mut channels = vec![("power", &self.power), ("state", &self.state)];
+        if let Some(ref temperature) = self.temperature {
+            channels.push(("temperature", temperature));
+        }
+        if let Some(ref sensed) = self.sensed {
+            channels.push(("sensed_temperature", sensed));
+        }
+        if let Some(ref peak) = self.peak {
+            channels.push(("peak_temperature", peak));
+        }
+        if let Some(ref damage) = self.damage {
+            channels.push(("damage", damage));
+        }
+        if let Some(ref current) = self.current {
+            channels.push(("current", current));
+        }
+        if let Some(ref droop) = self.droop {
+            channels.push(("droop", droop));
+        }
+        for &(ref name, ref profile) in &self.metrics {
+            channels.push((name, profile));
+        }
+        channels
+    }
+}
@@ -0,0 +1,109 @@
+use {Config, Result};
+
+/// A dynamic thermal management.
+///
+/// A processing element is considered hot once its temperature exceeds the
+/// threshold, and it is considered cool again once its temperature drops below
+/// the threshold minus the hysteresis.
+pub struct Dtm {
+    /// The policy.
+    pub policy: DtmPolicy,
+    threshold: f64,
+    hysteresis: f64,
+    hot: Vec<bool>,
+}
+
+/// The action taken with respect to a hot processing element.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DtmPolicy {
+    /// Stop the processing element until it cools down.
+    StopAndGo,
+    /// Lower the voltage and frequency of the processing element.
+    Throttling {
+        /// The relative speed.
+        speed: f64,
+        /// The relative dynamic power.
+        power: f64,
+    },
+    /// Move the work of the processing element to the coolest idle processing
+    /// element of the same type, if any, and stop it otherwise.
+    Migration,
+}
+
+impl Dtm {
+    /// Create a thermal management.
+    pub fn new(element_count: usize, config: &Config) -> Result<Dtm> {
+        let policy = match config.get::<String>("policy") {
+            Some(policy) if policy == "stop_and_go" => DtmPolicy::StopAndGo,
+            Some(policy) if policy == "throttling" => {
+                let speed = *some!(config.get::<f64>("speed"),
+                                   "the speed of the throttling policy is required");
+                if speed <= 0.0 || speed > 1.0 {
+                    raise!("the speed of the throttling policy should be in (0, 1]");
+                }
+                let power = *config.get::<f64>("power").unwrap_or(&(speed * speed * speed));
+                if power < 0.0 {
+                    raise!("the power of the throttling policy should be nonnegative");
+                }
+                DtmPolicy::Throttling { speed: speed, power: power }
+            },
+            Some(policy) if policy == "migration" => DtmPolicy::Migration,
+            Some(policy) => raise!("found an unknown thermal-management policy ({:?})", policy),
+            _ => raise!("a thermal-management policy is required"),
+        };
+        let threshold = *some!(config.get::<f64>("threshold"),
+                               "the threshold of the thermal management is required");
+        let hysteresis = *config.get::<f64>("hysteresis").unwrap_or(&0.0);
+        if hysteresis < 0.0 {
+            raise!("the hysteresis of the thermal management should be nonnegative");
+        }
+        Ok(Dtm {
+            policy: policy,
+            threshold: threshold,
+            hysteresis: hysteresis,
+            hot: vec![false; element_count],
+        })
+    }
+
+    /// Check if a processing element is hot.
+    #[inline]
+    pub fn is_hot(&self, id: usize) -> bool {
+        self.hot[id]
+    }
+
+    /// Take into account the temperature of a time step and return the
+    /// processing elements that have become hot or cool.
+    pub fn next(&mut self, temperature: &[f64]) -> Vec<(usize, bool)> {
+        debug_assert_eq!(temperature.len(), self.hot.len());
+        let mut changes = vec![];
+        for (i, &temperature) in temperature.iter().enumerate() {
+            if !self.hot[i] && temperature > self.threshold {
+                self.hot[i] = true;
+                changes.push((i, true));
+            } else if self.hot[i] && temperature < self.threshold - self.hysteresis {
+                self.hot[i] = false;
+                changes.push((i, false));
+            }
+        }
+        changes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Dtm, DtmPolicy};
+
+    #[test]
+    fn next() {
+        let mut dtm = Dtm {
+            policy: DtmPolicy::StopAndGo,
+            threshold: 350.0,
+            hysteresis: 5.0,
+            hot: vec![false; 2],
+        };
+        assert_eq!(dtm.next(&[340.0, 351.0]), vec![(1, true)]);
+        assert_eq!(dtm.next(&[352.0, 348.0]), vec![(0, true)]);
+        assert_eq!(dtm.next(&[346.0, 344.0]), vec![(1, false)]);
+        assert!(dtm.is_hot(0) && !dtm.is_hot(1));
+    }
+}
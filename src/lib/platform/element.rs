@@ -1,64 +1,529 @@
-use std::str::FromStr;
+use std::fmt;
 
-use {Error, Result};
+use {Config, Result};
+use list;
+use platform::LeakageSource;
 
 /// A processing element of a platform.
-#[derive(Clone, Copy, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct Element {
     /// The identifier.
     pub id: usize,
+    /// The name.
+    pub name: String,
     /// The type.
     pub kind: ElementKind,
+    /// The family of the type.
+    pub family: ElementKind,
+    /// The performance relative to the other types of the family.
+    pub performance: f64,
+    /// The dynamic power relative to the other types of the family.
+    pub power: f64,
+    /// The capacity.
+    pub capacity: ElementCapacity,
     /// The area.
     pub area: f64,
+    /// The index of the die.
+    pub die: usize,
+    /// The bounding box if known.
+    pub bounds: Option<Bounds>,
 }
 
-/// The type of a processing element.
-#[derive(Clone, Copy, Debug, Eq, PartialEq)]
-pub enum ElementKind {
-    /// A central processing unit.
-    Core,
-    /// An L3 cache.
-    L3,
+/// The bounding box of a processing element on its die.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Bounds {
+    /// The distance from the left edge of the die.
+    pub left: f64,
+    /// The distance from the bottom edge of the die.
+    pub bottom: f64,
+    /// The width.
+    pub width: f64,
+    /// The height.
+    pub height: f64,
 }
 
+/// The type of a processing element.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct ElementKind(String);
+
 /// The capacity of a processing element.
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub enum ElementCapacity {
+    /// Incapable of hosting jobs.
+    Zero,
     /// Capable of hosting only one job at a time.
     Single,
+    /// Capable of hosting a limited number of jobs at a time.
+    Finite(usize),
     /// Capable of hosting as many jobs at a time as needed.
     Infinite,
 }
 
+/// The rule for combining the power of jobs sharing a processing element.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PowerCombination {
+    /// The power of the jobs adds up.
+    Additive,
+    /// The power of the jobs saturates.
+    ///
+    /// The total power of `n` jobs is scaled by `(1 + contention * (n - 1)) /
+    /// n`; hence, a contention of one corresponds to the additive rule, and a
+    /// contention of zero corresponds to the average power of the jobs.
+    Saturating {
+        /// The contention factor.
+        contention: f64,
+    },
+}
+
+/// A class of processing elements.
+///
+/// Workload patterns profiled on one type of processing elements can run on
+/// any other type of the same family, such as big and little cores. In that
+/// case, the speed and the dynamic power of the patterns are scaled by the
+/// ratios of the relative performance and power of the two types.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ElementClass {
+    /// The type.
+    pub kind: ElementKind,
+    /// The family.
+    pub family: ElementKind,
+    /// The relative performance.
+    pub performance: f64,
+    /// The relative dynamic power.
+    pub power: f64,
+    /// The patterns of the names of the processing elements.
+    pub patterns: Vec<String>,
+    /// The capacity.
+    pub capacity: ElementCapacity,
+    /// The rule for combining the power of jobs.
+    pub combination: PowerCombination,
+    /// The source of leakage power.
+    pub leakage: LeakageSource,
+}
+
+/// A classifier of processing elements by their names.
+#[derive(Clone, Debug)]
+pub struct ElementClassifier {
+    classes: Vec<ElementClass>,
+}
+
 impl Element {
     /// Create an element.
     #[inline]
-    pub fn new(id: usize, kind: ElementKind, area: f64) -> Element {
-        Element { id: id, kind: kind, area: area }
+    pub fn new(id: usize, name: String, kind: ElementKind, capacity: ElementCapacity, area: f64,
+               die: usize) -> Element {
+        Element {
+            id: id,
+            name: name,
+            family: kind.clone(),
+            kind: kind,
+            performance: 1.0,
+            power: 1.0,
+            capacity: capacity,
+            area: area,
+            die: die,
+            bounds: None,
+        }
+    }
+
+    /// Check if two processing elements reside on the same die and share an
+    /// edge, which requires their bounding boxes to be known.
+    pub fn is_adjacent(&self, other: &Element) -> bool {
+        match (self.bounds, other.bounds) {
+            (Some(one), Some(other_bounds)) if self.die == other.die => {
+                one.is_adjacent(&other_bounds)
+            },
+            _ => false,
+        }
     }
 
     /// Return the capacity of the processing element.
     #[inline(always)]
     pub fn capacity(&self) -> ElementCapacity {
-        if self.kind == ElementKind::Core {
-            ElementCapacity::Single
+        self.capacity
+    }
+}
+
+impl Bounds {
+    /// Compute the area shared with another bounding box.
+    #[inline]
+    pub fn overlap(&self, other: &Bounds) -> f64 {
+        overlap(self.left, self.width, other.left, other.width) *
+            overlap(self.bottom, self.height, other.bottom, other.height)
+    }
+
+    /// Find the piece of an edge shared with another bounding box and return
+    /// its length along with the distance between the centers of the boxes
+    /// across the edge.
+    pub fn contact(&self, other: &Bounds) -> Option<(f64, f64)> {
+        let (one, other) = (self, other);
+        let (length, distance) = if touch(one.left + one.width, other.left) ||
+                                    touch(other.left + other.width, one.left) {
+            (overlap(one.bottom, one.height, other.bottom, other.height),
+             (one.width + other.width) / 2.0)
+        } else if touch(one.bottom + one.height, other.bottom) ||
+                  touch(other.bottom + other.height, one.bottom) {
+            (overlap(one.left, one.width, other.left, other.width),
+             (one.height + other.height) / 2.0)
+        } else {
+            return None;
+        };
+        if length > EPSILON {
+            Some((length, distance))
         } else {
-            ElementCapacity::Infinite
+            None
+        }
+    }
+
+    /// Check if two bounding boxes share a piece of an edge.
+    #[inline]
+    pub fn is_adjacent(&self, other: &Bounds) -> bool {
+        self.contact(other).is_some()
+    }
+}
+
+impl ElementKind {
+    /// Create a type.
+    #[inline]
+    pub fn new<T: Into<String>>(name: T) -> ElementKind {
+        ElementKind(name.into().to_lowercase())
+    }
+
+    /// Return the name.
+    #[inline]
+    pub fn name(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for ElementKind {
+    #[inline]
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        self.0.fmt(formatter)
+    }
+}
+
+impl ElementClass {
+    /// Create a class.
+    ///
+    /// The patterns of the names are given by `pattern` or by `patterns` as a
+    /// comma-separated string or an array of tables with a `pattern` key.
+    pub fn new(config: &Config) -> Result<ElementClass> {
+        let name = some!(config.get::<String>("name"), "the name of an element type is required");
+        let patterns = match try!(list::read::<String>(config, "patterns", "pattern")) {
+            Some(patterns) => patterns.iter().map(|pattern| pattern.to_lowercase()).collect(),
+            _ => match config.get::<String>("pattern") {
+                Some(pattern) => vec![pattern.to_lowercase()],
+                _ => vec![format!("{}*", name.to_lowercase())],
+            },
+        };
+        if patterns.is_empty() {
+            raise!("found no name patterns for the element type {:?}", name);
+        }
+        let family = match config.get::<String>("family") {
+            Some(family) => ElementKind::new(&family[..]),
+            _ => ElementKind::new(&name[..]),
+        };
+        let performance = *config.get::<f64>("performance").unwrap_or(&1.0);
+        let power = *config.get::<f64>("power").unwrap_or(&1.0);
+        if performance <= 0.0 || power <= 0.0 {
+            raise!("found an invalid performance or power for the element type {:?}", name);
+        }
+        let capacity = match (config.get::<i64>("capacity"), config.get::<String>("capacity")) {
+            (Some(&0), _) => ElementCapacity::Zero,
+            (Some(&1), _) => ElementCapacity::Single,
+            (Some(&count), _) if count > 1 => ElementCapacity::Finite(count as usize),
+            (_, Some(capacity)) => match &capacity[..] {
+                "zero" | "none" => ElementCapacity::Zero,
+                "single" => ElementCapacity::Single,
+                "infinite" => ElementCapacity::Infinite,
+                _ => raise!("found an unknown element capacity ({:?})", capacity),
+            },
+            (None, None) => ElementCapacity::Single,
+            _ => raise!("found an invalid capacity for the element type {:?}", name),
+        };
+        let combination = match config.get::<String>("combination") {
+            Some(rule) if rule == "additive" => PowerCombination::Additive,
+            Some(rule) if rule == "saturating" => {
+                let contention = *some!(config.get::<f64>("contention"),
+                                        "a contention factor is required for the saturating \
+                                         rule");
+                if !(contention >= 0.0 && contention <= 1.0) {
+                    raise!("the contention factor of the element type {:?} should be between \
+                            zero and one", name);
+                }
+                PowerCombination::Saturating { contention: contention }
+            },
+            Some(rule) => raise!("found an unknown power-combination rule ({:?})", rule),
+            _ => PowerCombination::Additive,
+        };
+        let leakage = match (config.get::<f64>("leakage"), config.get::<String>("leakage"),
+                             config.get::<i64>("leakage")) {
+            (Some(&density), _, _) if density >= 0.0 => LeakageSource::Density(density),
+            (Some(_), _, _) => {
+                raise!("the leakage density of the element type {:?} should be nonnegative", name);
+            },
+            (_, Some(source), _) if source == "pattern" => LeakageSource::Pattern,
+            (_, Some(source), _) if source == "none" => LeakageSource::None,
+            (None, None, None) => LeakageSource::Pattern,
+            (_, _, Some(_)) => {
+                raise!("the leakage density of the element type {:?} should be a \
+                        floating-point number", name);
+            },
+            _ => raise!("found an unknown leakage source for the element type {:?}", name),
+        };
+        Ok(ElementClass {
+            kind: ElementKind::new(&name[..]),
+            family: family,
+            performance: performance,
+            power: power,
+            patterns: patterns,
+            capacity: capacity,
+            combination: combination,
+            leakage: leakage,
+        })
+    }
+
+    /// Check if the class covers a name.
+    pub fn matches(&self, name: &str) -> bool {
+        let name = name.to_lowercase();
+        self.patterns.iter().any(|pattern| matches(pattern.as_bytes(), name.as_bytes()))
+    }
+}
+
+impl PowerCombination {
+    /// Compute the scaling factor of the total power of a number of jobs.
+    pub fn scale(&self, count: f64) -> f64 {
+        match self {
+            &PowerCombination::Saturating { contention } if count > 1.0 => {
+                (1.0 + contention * (count - 1.0)) / count
+            },
+            _ => 1.0,
+        }
+    }
+}
+
+impl ElementClassifier {
+    /// Create a classifier.
+    ///
+    /// The classes listed in the `kinds` section take precedence over the
+    /// default ones, which are cores (`core*`) and L3 caches (`l3*`). The
+    /// section is expected at the root of the configuration so that the
+    /// platform and the workload share the same classes.
+    pub fn new(config: &Config) -> Result<ElementClassifier> {
+        let mut classes = vec![];
+        if let Some(ref configs) = config.forest("kinds") {
+            for config in configs {
+                classes.push(try!(ElementClass::new(config)));
+            }
+        }
+        for class in ElementClassifier::default().classes {
+            if classes.iter().all(|other| other.kind != class.kind) {
+                classes.push(class);
+            }
+        }
+        Ok(ElementClassifier { classes: classes })
+    }
+
+    /// Find the class of a processing element given its name.
+    pub fn classify(&self, name: &str) -> Result<&ElementClass> {
+        match self.classes.iter().find(|class| class.matches(name)) {
+            Some(class) => Ok(class),
+            _ => raise!("found an unknown element id ({:?})", name),
+        }
+    }
+
+    /// Find the class of a type.
+    #[inline]
+    pub fn find(&self, kind: &ElementKind) -> Option<&ElementClass> {
+        self.classes.iter().find(|class| &class.kind == kind)
+    }
+
+    /// Create a processing element given its name.
+    pub fn element(&self, id: usize, name: &str, area: f64, die: usize) -> Result<Element> {
+        let class = try!(self.classify(name));
+        let mut element = Element::new(id, name.to_string(), class.kind.clone(), class.capacity,
+                                       area, die);
+        element.family = class.family.clone();
+        element.performance = class.performance;
+        element.power = class.power;
+        Ok(element)
+    }
+}
+
+impl Default for ElementClassifier {
+    fn default() -> ElementClassifier {
+        ElementClassifier {
+            classes: vec![
+                ElementClass {
+                    kind: ElementKind::new("core"),
+                    family: ElementKind::new("core"),
+                    performance: 1.0,
+                    power: 1.0,
+                    patterns: vec!["core*".to_string()],
+                    capacity: ElementCapacity::Single,
+                    combination: PowerCombination::Additive,
+                    leakage: LeakageSource::Pattern,
+                },
+                ElementClass {
+                    kind: ElementKind::new("l3"),
+                    family: ElementKind::new("l3"),
+                    performance: 1.0,
+                    power: 1.0,
+                    patterns: vec!["l3*".to_string()],
+                    capacity: ElementCapacity::Infinite,
+                    combination: PowerCombination::Additive,
+                    leakage: LeakageSource::Pattern,
+                },
+            ],
         }
     }
 }
 
-impl FromStr for ElementKind {
-    type Err = Error;
+/// Check if a name matches a pattern in which `*` stands for any sequence of
+/// characters and `?` for any single character.
+pub fn matches(pattern: &[u8], name: &[u8]) -> bool {
+    match (pattern.first(), name.first()) {
+        (None, None) => true,
+        (Some(&b'*'), _) => {
+            matches(&pattern[1..], name) || (!name.is_empty() && matches(pattern, &name[1..]))
+        },
+        (Some(&b'?'), Some(_)) => matches(&pattern[1..], &name[1..]),
+        (Some(one), Some(other)) if one == other => matches(&pattern[1..], &name[1..]),
+        _ => false,
+    }
+}
+
+const EPSILON: f64 = 1e-9;
+
+#[inline]
+fn overlap(start1: f64, length1: f64, start2: f64, length2: f64) -> f64 {
+    ((start1 + length1).min(start2 + length2) - start1.max(start2)).max(0.0)
+}
+
+#[inline]
+fn touch(one: f64, other: f64) -> bool {
+    (one - other).abs() <= EPSILON
+}
+
+#[cfg(test)]
+mod tests {
+    use configuration::format::TOML;
+    use platform::{Bounds, ElementCapacity, ElementKind, LeakageSource, PowerCombination};
+    use super::ElementClassifier;
+
+    #[test]
+    fn bounds() {
+        macro_rules! bounds(
+            ($left:expr, $bottom:expr, $width:expr, $height:expr) => (
+                Bounds { left: $left, bottom: $bottom, width: $width, height: $height }
+            );
+        );
+        let one = bounds!(0.0, 0.0, 1.0, 1.0);
+        assert!(one.is_adjacent(&bounds!(1.0, 0.5, 1.0, 1.0)));
+        assert!(one.is_adjacent(&bounds!(0.0, 1.0, 2.0, 1.0)));
+        assert!(!one.is_adjacent(&bounds!(1.0, 1.0, 1.0, 1.0)));
+        assert!(!one.is_adjacent(&bounds!(2.0, 0.0, 1.0, 1.0)));
+
+        assert_eq!(one.contact(&bounds!(1.0, 0.5, 2.0, 1.0)), Some((0.5, 1.5)));
+        assert_eq!(one.contact(&bounds!(0.0, -2.0, 2.0, 2.0)), Some((1.0, 1.5)));
+        assert_eq!(one.overlap(&bounds!(0.5, 0.5, 1.0, 1.0)), 0.25);
+        assert_eq!(one.overlap(&bounds!(1.0, 0.0, 1.0, 1.0)), 0.0);
+    }
+
+    #[test]
+    fn classify() {
+        let classifier = ElementClassifier::default();
+
+        let class = classifier.classify("Core0").unwrap();
+        assert_eq!(class.kind, ElementKind::new("core"));
+        assert_eq!(class.capacity, ElementCapacity::Single);
+
+        let class = classifier.classify("L30").unwrap();
+        assert_eq!(class.kind, ElementKind::new("l3"));
+        assert_eq!(class.capacity, ElementCapacity::Infinite);
+
+        assert!(classifier.classify("GPU0").is_err());
+    }
+
+    #[test]
+    fn configure() {
+        let config = TOML::parse(r#"
+            [[kinds]]
+            name = "big"
+            pattern = "b*"
+            family = "core"
+
+            [[kinds]]
+            name = "little"
+            patterns = "l?, small*"
+            family = "core"
+            leakage = 1.5
+
+            [[kinds]]
+            name = "dark"
+
+            [[kinds.patterns]]
+            pattern = "dark*"
+
+            [[kinds.patterns]]
+            pattern = "dead*"
+        "#).unwrap();
+        let classifier = ElementClassifier::new(&config).unwrap();
+        assert_eq!(classifier.classify("B0").unwrap().kind, ElementKind::new("big"));
+        let class = classifier.classify("Small1").unwrap();
+        assert_eq!(class.kind, ElementKind::new("little"));
+        assert_eq!(class.family, ElementKind::new("core"));
+        assert_eq!(class.leakage, LeakageSource::Density(1.5));
+        assert_eq!(classifier.classify("L2").unwrap().kind, ElementKind::new("little"));
+        assert_eq!(classifier.classify("Dead0").unwrap().kind, ElementKind::new("dark"));
+        assert_eq!(classifier.classify("Core0").unwrap().kind, ElementKind::new("core"));
+
+        let config = TOML::parse(r#"
+            [[kinds]]
+            name = "dark"
+            leakage = 0
+        "#).unwrap();
+        assert!(ElementClassifier::new(&config).is_err());
 
-    fn from_str(id: &str) -> Result<Self> {
-        let lower = id.to_lowercase();
-        if lower.starts_with("core") {
-            return Ok(ElementKind::Core);
-        } else if lower.starts_with("l3") {
-            return Ok(ElementKind::L3);
+        let config = TOML::parse(r#"
+            [[kinds]]
+            name = "dark"
+            leakage = -1.5
+        "#).unwrap();
+        match ElementClassifier::new(&config) {
+            Err(error) => assert!(error.to_string().ends_with("should be nonnegative")),
+            _ => unreachable!(),
         }
-        raise!("found an unknown element id ({:?})", id);
+
+        for contention in &["-0.5", "1.5"] {
+            let config = TOML::parse(&format!(r#"
+                [[kinds]]
+                name = "l3"
+                combination = "saturating"
+                contention = {}
+            "#, contention)).unwrap();
+            assert!(ElementClassifier::new(&config).is_err());
+        }
+    }
+
+    #[test]
+    fn combine() {
+        let combination = PowerCombination::Saturating { contention: 0.5 };
+        assert_eq!(combination.scale(0.0), 1.0);
+        assert_eq!(combination.scale(1.0), 1.0);
+        assert_eq!(combination.scale(2.0), 0.75);
+        assert_eq!(combination.scale(4.0), 0.625);
+        assert_eq!(PowerCombination::Additive.scale(4.0), 1.0);
+    }
+
+    #[test]
+    fn matches() {
+        assert!(super::matches(b"core*", b"core"));
+        assert!(super::matches(b"core*", b"core12"));
+        assert!(super::matches(b"*_router", b"noc_router"));
+        assert!(super::matches(b"l?", b"l2"));
+        assert!(!super::matches(b"l?", b"l"));
+        assert!(!super::matches(b"core*", b"l3"));
     }
 }
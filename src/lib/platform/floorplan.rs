@@ -0,0 +1,213 @@
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+use std::str::FromStr;
+
+use Result;
+use platform::{Bounds, Element, ElementClassifier};
+
+/// A floorplan.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Floorplan {
+    /// The blocks.
+    pub blocks: Vec<Block>,
+}
+
+/// A block of a floorplan.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Block {
+    /// The name.
+    pub name: String,
+    /// The width.
+    pub width: f64,
+    /// The height.
+    pub height: f64,
+    /// The distance from the left edge of the die.
+    pub left: f64,
+    /// The distance from the bottom edge of the die.
+    pub bottom: f64,
+}
+
+impl Floorplan {
+    /// Read a floorplan in the HotSpot format.
+    pub fn open<T: AsRef<Path>>(path: T) -> Result<Floorplan> {
+        let mut content = String::new();
+        ok!(ok!(File::open(path)).read_to_string(&mut content));
+        let mut blocks = vec![];
+        for line in content.lines() {
+            let line = match line.find('#') {
+                Some(i) => &line[..i],
+                _ => line,
+            };
+            let chunks = line.split_whitespace().collect::<Vec<_>>();
+            if chunks.is_empty() {
+                continue;
+            }
+            if chunks.len() < 5 {
+                raise!("found a malformed floorplan block ({:?})", line.trim());
+            }
+            macro_rules! get(
+                ($index:expr) => (match f64::from_str(chunks[$index]) {
+                    Ok(value) if value >= 0.0 => value,
+                    _ => raise!("found a malformed floorplan block ({:?})", line.trim()),
+                });
+            );
+            blocks.push(Block {
+                name: chunks[0].to_string(),
+                width: get!(1),
+                height: get!(2),
+                left: get!(3),
+                bottom: get!(4),
+            });
+        }
+        if blocks.is_empty() {
+            raise!("found a floorplan without blocks");
+        }
+        Ok(Floorplan { blocks: blocks })
+    }
+
+    /// Read a floorplan in the 3D-ICE format.
+    ///
+    /// An element composed of several rectangles is represented by their
+    /// bounding box. The dimensions are converted from micrometers to meters.
+    pub fn open_threed_ice<T: AsRef<Path>>(path: T) -> Result<Floorplan> {
+        let mut content = String::new();
+        ok!(ok!(File::open(path)).read_to_string(&mut content));
+        let mut blocks: Vec<Block> = vec![];
+        let mut corner = None;
+        for statement in content.split(';') {
+            let mut statement = statement.trim();
+            if let Some(i) = statement.find(':') {
+                let name = statement[..i].trim();
+                if name.is_empty() || name.contains(char::is_whitespace) {
+                    raise!("found a malformed floorplan element ({:?})", name);
+                }
+                blocks.push(Block {
+                    name: name.to_string(),
+                    width: 0.0,
+                    height: 0.0,
+                    left: 0.0,
+                    bottom: 0.0,
+                });
+                statement = statement[(i + 1)..].trim();
+            }
+            let (keyword, rest) = match statement.find(|c: char| c.is_whitespace() || c == '(') {
+                Some(i) => (&statement[..i], &statement[i..]),
+                _ => continue,
+            };
+            let mut numbers = vec![];
+            let delimiter = |c: char| c == ',' || c == '(' || c == ')' || c.is_whitespace();
+            for chunk in rest.split(delimiter).filter(|chunk| !chunk.is_empty()) {
+                match f64::from_str(chunk) {
+                    Ok(value) => numbers.push(value / 1e6),
+                    _ => break,
+                }
+            }
+            let block = match blocks.last_mut() {
+                Some(block) => block,
+                _ => continue,
+            };
+            let n = &numbers;
+            match (keyword, numbers.len()) {
+                ("position", 2) => corner = Some((n[0], n[1])),
+                ("dimension", 2) => match corner.take() {
+                    Some((x, y)) => block.extend(x, y, n[0], n[1]),
+                    _ => raise!("found a dimension without a position ({:?})", block.name),
+                },
+                ("rectangle", 4) => block.extend(n[0], n[1], n[2], n[3]),
+                ("position", _) | ("dimension", _) | ("rectangle", _) => {
+                    raise!("found a malformed floorplan element ({:?})", block.name);
+                },
+                _ => {},
+            }
+        }
+        if blocks.is_empty() {
+            raise!("found a floorplan without elements");
+        }
+        if let Some(block) = blocks.iter().find(|block| block.width == 0.0) {
+            raise!("found a floorplan element without dimensions ({:?})", block.name);
+        }
+        Ok(Floorplan { blocks: blocks })
+    }
+
+    /// Convert the blocks into processing elements residing on a die.
+    pub fn elements(&self, die: usize, classifier: &ElementClassifier) -> Result<Vec<Element>> {
+        let mut elements = vec![];
+        for block in &self.blocks {
+            let id = elements.len();
+            let mut element = try!(classifier.element(id, &block.name, block.area(), die));
+            element.bounds = Some(block.bounds());
+            elements.push(element);
+        }
+        Ok(elements)
+    }
+}
+
+impl Block {
+    /// Return the area.
+    #[inline]
+    pub fn area(&self) -> f64 {
+        self.width * self.height
+    }
+
+    /// Return the bounding box.
+    #[inline]
+    pub fn bounds(&self) -> Bounds {
+        Bounds { left: self.left, bottom: self.bottom, width: self.width, height: self.height }
+    }
+
+    fn extend(&mut self, left: f64, bottom: f64, width: f64, height: f64) {
+        if self.width == 0.0 && self.height == 0.0 {
+            self.left = left;
+            self.bottom = bottom;
+            self.width = width;
+            self.height = height;
+            return;
+        }
+        let right = (self.left + self.width).max(left + width);
+        let top = (self.bottom + self.height).max(bottom + height);
+        self.left = self.left.min(left);
+        self.bottom = self.bottom.min(bottom);
+        self.width = right - self.left;
+        self.height = top - self.bottom;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Block, Floorplan};
+
+    #[test]
+    fn open() {
+        let floorplan = Floorplan::open("tests/fixtures/004_hotspot.flp").unwrap();
+        assert_eq!(floorplan.blocks.len(), 5);
+        assert_eq!(floorplan.blocks[1], Block {
+            name: "Core1".to_string(),
+            width: 3976e-6,
+            height: 7950e-6,
+            left: 3976e-6,
+            bottom: 0.0,
+        });
+        assert_eq!(floorplan.blocks[4], Block {
+            name: "L30".to_string(),
+            width: 15904e-6,
+            height: 3894e-6,
+            left: 0.0,
+            bottom: 7950e-6,
+        });
+    }
+
+    #[test]
+    fn open_threed_ice() {
+        let one = Floorplan::open("tests/fixtures/004_hotspot.flp").unwrap();
+        let other = Floorplan::open_threed_ice("tests/fixtures/004.flp").unwrap();
+        assert_eq!(one.blocks.len(), other.blocks.len());
+        for (one, other) in one.blocks.iter().zip(&other.blocks) {
+            assert_eq!(one.name, other.name);
+            assert!((one.width - other.width).abs() < 1e-12);
+            assert!((one.height - other.height).abs() < 1e-12);
+            assert!((one.left - other.left).abs() < 1e-12);
+            assert!((one.bottom - other.bottom).abs() < 1e-12);
+        }
+    }
+}
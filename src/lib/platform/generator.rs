@@ -0,0 +1,190 @@
+use std::fs::File;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use {Config, Result};
+use platform::{Block, Compact, Floorplan};
+
+/// A generator of synthetic platforms.
+///
+/// The die consists of a grid of identical cores, numbered row by row starting
+/// from the bottom-left corner, with an optional L3 strip spanning the whole
+/// width along the top edge. The materials and the cooling are those of the
+/// compact thermal model.
+///
+/// If `output` is given, the thermal platform writes the generated stack
+/// description there, and the floorplan next to it with the `.flp` extension,
+/// and simulates them with 3D-ICE.
+#[derive(Clone, Debug)]
+pub struct Generator {
+    /// The number of rows of cores.
+    pub rows: usize,
+    /// The number of columns of cores.
+    pub columns: usize,
+    /// The width of a core.
+    pub core_width: f64,
+    /// The height of a core.
+    pub core_height: f64,
+    /// The height of the L3 strip (zero for none).
+    pub l3_height: f64,
+    /// The width of a thermal cell of 3D-ICE.
+    pub cell_width: f64,
+    /// The height of a thermal cell of 3D-ICE.
+    pub cell_height: f64,
+    /// The materials and the cooling.
+    pub model: Compact,
+    /// The path to the stack description to write.
+    pub output: Option<PathBuf>,
+}
+
+impl Generator {
+    /// Create a generator.
+    pub fn new(config: &Config) -> Result<Generator> {
+        let rows = *some!(config.get::<i64>("rows"), "the number of rows of cores is required");
+        let columns = *some!(config.get::<i64>("columns"),
+                             "the number of columns of cores is required");
+        if rows <= 0 || columns <= 0 {
+            raise!("the number of rows and columns of cores should be positive");
+        }
+        let core_width = *some!(config.get::<f64>("core_width"),
+                                "the width of a core is required");
+        let core_height = *some!(config.get::<f64>("core_height"),
+                                 "the height of a core is required");
+        if core_width <= 0.0 || core_height <= 0.0 {
+            raise!("the dimensions of a core should be positive");
+        }
+        let l3_height = *config.get::<f64>("l3_height").unwrap_or(&0.0);
+        if l3_height < 0.0 {
+            raise!("the height of the L3 strip should be nonnegative");
+        }
+        let cell_width = *config.get::<f64>("cell_width").unwrap_or(&(core_width / 2.0));
+        let cell_height = *config.get::<f64>("cell_height").unwrap_or(&(core_height / 2.0));
+        if cell_width <= 0.0 || cell_height <= 0.0 {
+            raise!("the dimensions of a thermal cell should be positive");
+        }
+        Ok(Generator {
+            rows: rows as usize,
+            columns: columns as usize,
+            core_width: core_width,
+            core_height: core_height,
+            l3_height: l3_height,
+            cell_width: cell_width,
+            cell_height: cell_height,
+            model: try!(Compact::new(config)),
+            output: match config.get::<String>("output") {
+                Some(_) => Some(path!(@unchecked config["output"], "an output path is required")),
+                _ => None,
+            },
+        })
+    }
+
+    /// Return the width and height of the die.
+    #[inline]
+    pub fn dimensions(&self) -> (f64, f64) {
+        (self.columns as f64 * self.core_width,
+         self.rows as f64 * self.core_height + self.l3_height)
+    }
+
+    /// Generate a floorplan.
+    pub fn floorplan(&self) -> Floorplan {
+        let mut blocks = vec![];
+        for i in 0..self.rows {
+            for j in 0..self.columns {
+                blocks.push(Block {
+                    name: format!("Core{}", i * self.columns + j),
+                    width: self.core_width,
+                    height: self.core_height,
+                    left: j as f64 * self.core_width,
+                    bottom: i as f64 * self.core_height,
+                });
+            }
+        }
+        if self.l3_height > 0.0 {
+            blocks.push(Block {
+                name: "L30".to_string(),
+                width: self.columns as f64 * self.core_width,
+                height: self.l3_height,
+                left: 0.0,
+                bottom: self.rows as f64 * self.core_height,
+            });
+        }
+        Floorplan { blocks: blocks }
+    }
+
+    /// Write a stack description and the corresponding floorplan in the
+    /// 3D-ICE format.
+    pub fn write_threed_ice<T: AsRef<Path>, U: AsRef<Path>>(&self, stack: T, floorplan: U,
+                                                            ambience: f64) -> Result<()> {
+        let (stack, floorplan) = (stack.as_ref(), floorplan.as_ref());
+        let &Compact { ref die, ref interface, ref spreader, ref sink, convection } = &self.model;
+        let (width, height) = self.dimensions();
+        let mut file = ok!(File::create(stack));
+        macro_rules! material(
+            ($name:expr, $layer:expr) => (
+                ok!(write!(file, "material {}:\n   thermal conductivity {:e};\n   \
+                                  volumetric heat capacity {:e};\n\n",
+                           $name, $layer.conductivity * 1e-6, $layer.capacity * 1e-18))
+            );
+        );
+        material!("SILICON", die);
+        material!("HEAT_SINK", sink);
+        material!("HEAT_SPREADER", spreader);
+        material!("INTERFACE_MATERIAL", interface);
+        ok!(write!(file, "heat sink:\n   sink height {:e}, area {:e}, material HEAT_SINK;\n   \
+                          spreader height {:e}, area {:e}, material HEAT_SPREADER;\n   \
+                          heat transfer coefficient {:e};\n   ambient temperature {};\n\n",
+                   sink.thickness * 1e6, sink.side * sink.side * 1e12,
+                   spreader.thickness * 1e6, spreader.side * spreader.side * 1e12,
+                   convection * 1e-12, ambience));
+        ok!(write!(file, "die DIE:\n   layer {:e} INTERFACE_MATERIAL;\n   \
+                          source {:e} SILICON;\n\n",
+                   interface.thickness * 1e6, die.thickness * 1e6));
+        ok!(write!(file, "dimensions:\n   chip length {:e}, width {:e};\n   \
+                          cell length {:e}, width {:e};\n\n",
+                   width * 1e6, height * 1e6, self.cell_width * 1e6, self.cell_height * 1e6));
+        ok!(write!(file, "stack:\n   die DIE1 DIE floorplan \"{}\";\n\n", floorplan.display()));
+        ok!(write!(file, "solver:\n   steady;\n   initial temperature {};\n", ambience));
+
+        let mut file = ok!(File::create(floorplan));
+        for block in &self.floorplan().blocks {
+            ok!(write!(file, "{}:\n  position {}, {};\n  dimension {}, {};\n\n  \
+                              power values 0;\n\n",
+                       block.name, block.left * 1e6, block.bottom * 1e6,
+                       block.width * 1e6, block.height * 1e6));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use configuration::format::TOML;
+    use platform::{Compact, Floorplan};
+    use super::Generator;
+
+    #[test]
+    fn floorplan() {
+        let config = TOML::open("tests/fixtures/streamer.toml").unwrap();
+        let floorplan = Floorplan::open("tests/fixtures/004_hotspot.flp").unwrap();
+        let generator = Generator {
+            rows: 1,
+            columns: 4,
+            core_width: 3976e-6,
+            core_height: 7950e-6,
+            l3_height: 3894e-6,
+            cell_width: 3976e-6,
+            cell_height: 5922e-6,
+            model: Compact::new(&config).unwrap(),
+            output: None,
+        };
+        let other = generator.floorplan();
+        assert_eq!(other.blocks.len(), floorplan.blocks.len());
+        for (one, other) in floorplan.blocks.iter().zip(&other.blocks) {
+            assert_eq!(one.name, other.name);
+            assert!((one.width - other.width).abs() < 1e-12);
+            assert!((one.height - other.height).abs() < 1e-12);
+            assert!((one.left - other.left).abs() < 1e-12);
+            assert!((one.bottom - other.bottom).abs() < 1e-12);
+        }
+    }
+}
@@ -0,0 +1,215 @@
+use {Config, Result};
+use platform::{Block, Floorplan};
+
+/// A regular grid of thermal cells covering the die of a floorplan.
+///
+/// The cells are numbered row by row starting from the bottom-left corner of
+/// the die. The power of a block is spread over the cells it overlaps in
+/// proportion to the overlap area.
+#[derive(Clone, Debug)]
+pub struct Grid {
+    /// The number of rows.
+    pub rows: usize,
+    /// The number of columns.
+    pub columns: usize,
+    /// The distance from the left edge of the die to the first column.
+    pub left: f64,
+    /// The distance from the bottom edge of the die to the first row.
+    pub bottom: f64,
+    /// The width of a cell.
+    pub width: f64,
+    /// The height of a cell.
+    pub height: f64,
+    overlaps: Vec<Vec<(usize, f64)>>,
+}
+
+/// A virtual temperature sensor placed at a point of the die.
+///
+/// Probes are read from the thermal grid of the compact thermal model, which
+/// has a single die.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Probe {
+    /// The distance from the left edge of the die.
+    pub x: f64,
+    /// The distance from the bottom edge of the die.
+    pub y: f64,
+}
+
+impl Grid {
+    /// Create a grid with a number of rows and columns.
+    pub fn new(floorplan: &Floorplan, rows: usize, columns: usize) -> Result<Grid> {
+        if rows == 0 || columns == 0 {
+            raise!("the number of rows and columns of a thermal grid should be positive");
+        }
+        let blocks = &floorplan.blocks;
+        let left = blocks.iter().fold(::std::f64::INFINITY, |left, block| left.min(block.left));
+        let bottom = blocks.iter().fold(::std::f64::INFINITY,
+                                        |bottom, block| bottom.min(block.bottom));
+        let right = blocks.iter().fold(0.0, |right: f64, block| {
+            right.max(block.left + block.width)
+        });
+        let top = blocks.iter().fold(0.0, |top: f64, block| {
+            top.max(block.bottom + block.height)
+        });
+        let mut grid = Grid {
+            rows: rows,
+            columns: columns,
+            left: left,
+            bottom: bottom,
+            width: (right - left) / columns as f64,
+            height: (top - bottom) / rows as f64,
+            overlaps: vec![],
+        };
+        if !(grid.width > 0.0 && grid.height > 0.0) {
+            raise!("found a floorplan without area");
+        }
+        let cells = grid.cells();
+        for block in blocks {
+            let (bounds, mut overlaps) = (block.bounds(), vec![]);
+            for (i, cell) in cells.iter().enumerate() {
+                let area = bounds.overlap(&cell.bounds());
+                if area > 0.0 {
+                    overlaps.push((i, area));
+                }
+            }
+            if overlaps.is_empty() {
+                raise!("the block {:?} does not overlap with the thermal grid", block.name);
+            }
+            grid.overlaps.push(overlaps);
+        }
+        Ok(grid)
+    }
+
+    /// Return the number of cells.
+    #[inline]
+    pub fn cell_count(&self) -> usize {
+        self.rows * self.columns
+    }
+
+    /// Return the cells as the blocks of a floorplan.
+    pub fn cells(&self) -> Vec<Block> {
+        let mut cells = Vec::with_capacity(self.cell_count());
+        for i in 0..self.rows {
+            for j in 0..self.columns {
+                cells.push(Block {
+                    name: format!("cell_{}_{}", i, j),
+                    width: self.width,
+                    height: self.height,
+                    left: self.left + (j as f64) * self.width,
+                    bottom: self.bottom + (i as f64) * self.height,
+                });
+            }
+        }
+        cells
+    }
+
+    /// Spread the power of the blocks over the cells.
+    pub fn distribute(&self, power: &[f64], cells: &mut [f64]) {
+        debug_assert_eq!(power.len(), self.overlaps.len());
+        debug_assert_eq!(cells.len(), self.cell_count());
+        for value in cells.iter_mut() {
+            *value = 0.0;
+        }
+        for (overlaps, &power) in self.overlaps.iter().zip(power) {
+            let total = overlaps.iter().fold(0.0, |total, &(_, area)| total + area);
+            for &(i, area) in overlaps {
+                cells[i] += power * area / total;
+            }
+        }
+    }
+
+    /// Compute the average temperature of each block.
+    pub fn average(&self, cells: &[f64], temperature: &mut [f64]) {
+        debug_assert_eq!(temperature.len(), self.overlaps.len());
+        for (overlaps, temperature) in self.overlaps.iter().zip(temperature) {
+            let (mut sum, mut total) = (0.0, 0.0);
+            for &(i, area) in overlaps {
+                sum += cells[i] * area;
+                total += area;
+            }
+            *temperature = sum / total;
+        }
+    }
+
+    /// Compute the peak temperature of each block, that is, the maximum over
+    /// the cells that the block overlaps.
+    pub fn peak(&self, cells: &[f64], temperature: &mut [f64]) {
+        debug_assert_eq!(temperature.len(), self.overlaps.len());
+        for (overlaps, temperature) in self.overlaps.iter().zip(temperature) {
+            *temperature = overlaps.iter().fold(::std::f64::NEG_INFINITY,
+                                                |peak, &(i, _)| peak.max(cells[i]));
+        }
+    }
+
+    /// Interpolate the temperature at a point.
+    ///
+    /// The temperature of a cell is attributed to its center, and the
+    /// temperature at a point is bilinearly interpolated between the four
+    /// closest centers. Outside the centers, the nearest values are taken.
+    pub fn interpolate(&self, cells: &[f64], x: f64, y: f64) -> f64 {
+        let (j, u) = locate((x - self.left) / self.width - 0.5, self.columns);
+        let (i, v) = locate((y - self.bottom) / self.height - 0.5, self.rows);
+        let (i1, j1) = ((i + 1).min(self.rows - 1), (j + 1).min(self.columns - 1));
+        let value = |i: usize, j: usize| cells[i * self.columns + j];
+        (1.0 - v) * ((1.0 - u) * value(i, j) + u * value(i, j1)) +
+        v * ((1.0 - u) * value(i1, j) + u * value(i1, j1))
+    }
+}
+
+impl Probe {
+    /// Create a probe.
+    pub fn new(config: &Config) -> Result<Probe> {
+        let x = *some!(config.get::<f64>("x"), "the x coordinate of a probe is required");
+        let y = *some!(config.get::<f64>("y"), "the y coordinate of a probe is required");
+        if x < 0.0 || y < 0.0 {
+            raise!("the coordinates of a probe should be nonnegative");
+        }
+        Ok(Probe { x: x, y: y })
+    }
+}
+
+fn locate(position: f64, count: usize) -> (usize, f64) {
+    if position <= 0.0 {
+        (0, 0.0)
+    } else if position >= (count - 1) as f64 {
+        (count - 1, 0.0)
+    } else {
+        let index = position.floor();
+        (index as usize, position - index)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use assert;
+    use platform::{Block, Floorplan};
+    use super::Grid;
+
+    #[test]
+    fn new() {
+        let floorplan = Floorplan {
+            blocks: vec![
+                Block { name: "A".to_string(), width: 2.0, height: 1.0, left: 0.0, bottom: 0.0 },
+                Block { name: "B".to_string(), width: 1.0, height: 1.0, left: 0.0, bottom: 1.0 },
+                Block { name: "C".to_string(), width: 1.0, height: 1.0, left: 1.0, bottom: 1.0 },
+            ],
+        };
+        let grid = Grid::new(&floorplan, 2, 4).unwrap();
+        assert_eq!((grid.width, grid.height), (0.5, 1.0));
+
+        let mut cells = vec![0.0; 8];
+        grid.distribute(&[4.0, 2.0, 1.0], &mut cells);
+        assert_eq!(&cells, &[1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 0.5, 0.5]);
+
+        let cells = vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0];
+        let mut temperature = vec![0.0; 3];
+        grid.average(&cells, &mut temperature);
+        assert_eq!(&temperature, &[2.5, 5.5, 7.5]);
+        grid.peak(&cells, &mut temperature);
+        assert_eq!(&temperature, &[4.0, 6.0, 8.0]);
+
+        assert::close(&[grid.interpolate(&cells, 0.0, 0.0), grid.interpolate(&cells, 1.0, 1.0),
+                        grid.interpolate(&cells, 0.5, 0.75), grid.interpolate(&cells, 2.0, 2.0)],
+                      &[1.0, 4.5, 2.5, 8.0], 1e-12);
+    }
+}
@@ -0,0 +1,304 @@
+use {Config, Result};
+use platform::{Element, Profile};
+
+// The occupancy below which a processing element is considered idle. It is
+// nonzero since retracted work might leave round-off residuals.
+const EPSILON: f64 = 1e-9;
+
+/// A manager of the idle states of processing elements.
+///
+/// An element that stays idle long enough is first clock gated and then power
+/// gated, provided that its type supports these states. Once work arrives, the
+/// element returns to the active state. The jobs are not postponed while the
+/// element is waking up; only the transition energy is accounted for.
+pub struct Idle {
+    policies: Vec<IdlePolicy>,
+    elements: Vec<Tracker>,
+}
+
+/// The state of a processing element.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ElementState {
+    /// Executing or ready to execute work.
+    Active = 0,
+    /// Idle with the clock gated.
+    ClockGated = 1,
+    /// Idle with the supply gated.
+    PowerGated = 2,
+}
+
+/// The idle states supported by a type of processing elements.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct IdlePolicy {
+    /// The clock-gated state.
+    pub clock_gated: Option<IdleState>,
+    /// The power-gated state.
+    pub power_gated: Option<IdleState>,
+}
+
+/// An idle state.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct IdleState {
+    /// The fraction of the leakage power drawn in the state.
+    pub fraction: f64,
+    /// The idle time after which the transition into the state begins.
+    pub timeout: f64,
+    /// The time needed to enter the state.
+    pub entry_latency: f64,
+    /// The energy consumed while entering the state.
+    pub entry_energy: f64,
+    /// The time needed to leave the state.
+    pub exit_latency: f64,
+    /// The energy consumed while leaving the state.
+    pub exit_energy: f64,
+}
+
+#[derive(Clone, Copy)]
+struct Tracker {
+    state: ElementState,
+    idle: f64,
+    waking: Option<(f64, IdleState)>,
+}
+
+impl Idle {
+    /// Create a manager given an optional configuration with a section per
+    /// element type.
+    pub fn new(elements: &[Element], config: Option<&Config>) -> Result<Idle> {
+        let mut policies = Vec::with_capacity(elements.len());
+        for element in elements {
+            let config = config.and_then(|config| config.branch(&element.kind.to_string()));
+            let policy = match config {
+                Some(ref config) => try!(IdlePolicy::new(config)),
+                _ => IdlePolicy::default(),
+            };
+            policies.push(policy);
+        }
+        let tracker = Tracker { state: ElementState::Active, idle: 0.0, waking: None };
+        Ok(Idle { policies: policies, elements: vec![tracker; elements.len()] })
+    }
+
+    /// Advance the states given the number of jobs per processing element and
+    /// time step, add the transition power to a power profile, and return the
+    /// states.
+    pub fn next(&mut self, occupancy: &Profile, power: &mut Profile) -> Profile {
+        let mut states = occupancy.clone_zero();
+        let (element_count, time_step) = (occupancy.element_count, occupancy.time_step);
+        for i in 0..occupancy.step_count {
+            for j in 0..element_count {
+                let k = i * element_count + j;
+                let (policy, tracker) = (&self.policies[j], &mut self.elements[j]);
+                let energy = if occupancy[k] > EPSILON {
+                    tracker.wake(policy);
+                    0.0
+                } else {
+                    tracker.sleep(policy, time_step)
+                };
+                power[k] += (energy + tracker.drain(time_step)) / time_step;
+                states[k] = tracker.state as usize as f64;
+            }
+        }
+        states
+    }
+
+    /// Return the fraction of the leakage power drawn by a processing element
+    /// in a state given by its code in a state profile.
+    pub fn fraction(&self, id: usize, state: f64) -> f64 {
+        let state = match state as usize {
+            1 => ElementState::ClockGated,
+            2 => ElementState::PowerGated,
+            _ => ElementState::Active,
+        };
+        self.policies[id].find(state).map(|state| state.fraction).unwrap_or(1.0)
+    }
+}
+
+impl IdlePolicy {
+    /// Create a policy.
+    pub fn new(config: &Config) -> Result<IdlePolicy> {
+        let policy = IdlePolicy {
+            clock_gated: match config.branch("clock_gated") {
+                Some(ref config) => Some(try!(IdleState::new(config))),
+                _ => None,
+            },
+            power_gated: match config.branch("power_gated") {
+                Some(ref config) => Some(try!(IdleState::new(config))),
+                _ => None,
+            },
+        };
+        if let (Some(one), Some(other)) = (policy.clock_gated, policy.power_gated) {
+            if one.timeout + one.entry_latency > other.timeout + other.entry_latency {
+                raise!("the power-gated state should be entered after the clock-gated one");
+            }
+        }
+        Ok(policy)
+    }
+
+    /// Return the idle state corresponding to a state.
+    pub fn find(&self, state: ElementState) -> Option<IdleState> {
+        match state {
+            ElementState::Active => None,
+            ElementState::ClockGated => self.clock_gated,
+            ElementState::PowerGated => self.power_gated,
+        }
+    }
+
+    fn deepest(&self, idle: f64) -> ElementState {
+        for &state in &[ElementState::PowerGated, ElementState::ClockGated] {
+            if let Some(IdleState { timeout, entry_latency, .. }) = self.find(state) {
+                if idle >= timeout + entry_latency {
+                    return state;
+                }
+            }
+        }
+        ElementState::Active
+    }
+}
+
+impl IdleState {
+    /// Create a state.
+    pub fn new(config: &Config) -> Result<IdleState> {
+        macro_rules! get(
+            ($name:expr) => (*config.get::<f64>($name).unwrap_or(&0.0));
+        );
+        let state = IdleState {
+            fraction: *some!(config.get::<f64>("fraction"),
+                             "the leakage fraction of an idle state is required"),
+            timeout: get!("timeout"),
+            entry_latency: get!("entry_latency"),
+            entry_energy: get!("entry_energy"),
+            exit_latency: get!("exit_latency"),
+            exit_energy: get!("exit_energy"),
+        };
+        if state.fraction < 0.0 || state.fraction > 1.0 {
+            raise!("the leakage fraction of an idle state should be between zero and one");
+        }
+        if state.timeout < 0.0 || state.entry_latency < 0.0 || state.exit_latency < 0.0 {
+            raise!("the timing of an idle state should be nonnegative");
+        }
+        if state.entry_energy < 0.0 || state.exit_energy < 0.0 {
+            raise!("the transition energy of an idle state should be nonnegative");
+        }
+        Ok(state)
+    }
+
+    fn entry(&self, from: f64, until: f64) -> f64 {
+        let (start, end) = (self.timeout, self.timeout + self.entry_latency);
+        if self.entry_latency > 0.0 {
+            let overlap = (until.min(end) - from.max(start)).max(0.0);
+            self.entry_energy * overlap / self.entry_latency
+        } else if from <= start && start < until {
+            self.entry_energy
+        } else {
+            0.0
+        }
+    }
+}
+
+impl Tracker {
+    fn sleep(&mut self, policy: &IdlePolicy, time_step: f64) -> f64 {
+        let (from, until) = (self.idle, self.idle + time_step);
+        self.idle = until;
+        self.state = policy.deepest(until);
+        policy.clock_gated.map(|state| state.entry(from, until)).unwrap_or(0.0) +
+        policy.power_gated.map(|state| state.entry(from, until)).unwrap_or(0.0)
+    }
+
+    fn wake(&mut self, policy: &IdlePolicy) {
+        if let Some(state) = policy.find(self.state) {
+            self.waking = Some((state.exit_latency, state));
+        }
+        self.state = ElementState::Active;
+        self.idle = 0.0;
+    }
+
+    fn drain(&mut self, time_step: f64) -> f64 {
+        let (remaining, state) = match self.waking {
+            Some(waking) => waking,
+            _ => return 0.0,
+        };
+        if remaining <= time_step || state.exit_latency == 0.0 {
+            self.waking = None;
+            if state.exit_latency == 0.0 {
+                return state.exit_energy;
+            }
+            return state.exit_energy * remaining / state.exit_latency;
+        }
+        self.waking = Some((remaining - time_step, state));
+        state.exit_energy * time_step / state.exit_latency
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use assert;
+    use platform::{ElementState, Profile};
+    use super::{Idle, IdlePolicy, IdleState, Tracker};
+
+    #[test]
+    fn next() {
+        let clock_gated = IdleState {
+            fraction: 0.5,
+            timeout: 0.0,
+            entry_latency: 1.0,
+            entry_energy: 2.0,
+            exit_latency: 0.0,
+            exit_energy: 1.0,
+        };
+        let power_gated = IdleState {
+            fraction: 0.0,
+            timeout: 2.0,
+            entry_latency: 2.0,
+            entry_energy: 4.0,
+            exit_latency: 2.0,
+            exit_energy: 2.0,
+        };
+        let mut idle = Idle {
+            policies: vec![
+                IdlePolicy { clock_gated: Some(clock_gated), power_gated: Some(power_gated) },
+                IdlePolicy::default(),
+            ],
+            elements: vec![Tracker { state: ElementState::Active, idle: 0.0, waking: None }; 2],
+        };
+
+        let mut occupancy = Profile::new(2, 1.0);
+        occupancy.step_count = 8;
+        occupancy.data = vec![
+            1.0, 0.0,
+            0.0, 0.0,
+            0.0, 0.0,
+            0.0, 0.0,
+            0.0, 0.0,
+            0.0, 0.0,
+            1.0, 0.0,
+            1.0, 0.0,
+        ];
+        let mut power = occupancy.clone_zero();
+        let states = idle.next(&occupancy, &mut power);
+
+        assert_eq!(&states.data, &vec![
+            0.0, 0.0,
+            1.0, 0.0,
+            1.0, 0.0,
+            1.0, 0.0,
+            2.0, 0.0,
+            2.0, 0.0,
+            0.0, 0.0,
+            0.0, 0.0,
+        ]);
+        assert::close(&power.data, &vec![
+            0.0, 0.0,
+            2.0, 0.0,
+            0.0, 0.0,
+            2.0, 0.0,
+            2.0, 0.0,
+            0.0, 0.0,
+            1.0, 0.0,
+            1.0, 0.0,
+        ], 1e-14);
+
+        assert_eq!(idle.fraction(0, 0.0), 1.0);
+        assert_eq!(idle.fraction(0, 1.0), 0.5);
+        assert_eq!(idle.fraction(0, 2.0), 0.0);
+        assert_eq!(idle.fraction(1, 2.0), 1.0);
+    }
+}
@@ -0,0 +1,60 @@
+use {Config, Result};
+
+/// A model of interference between co-running jobs.
+///
+/// Two running jobs interfere when they share a processing element that can
+/// host several jobs at a time or, if the scope is the die, when they run on
+/// the same die. The pressure exerted on a job is the sum of the intensities
+/// of the jobs interfering with it. A job of intensity `i` under pressure `p`
+/// runs at the speed `1 / (1 + slowdown * i * p)` of the nominal one, and its
+/// dynamic power is scaled by the speed raised to `exponent`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Interference {
+    /// The sensitivity of the speed to the pressure.
+    pub slowdown: f64,
+    /// The exponent relating the dynamic power to the speed.
+    pub exponent: f64,
+    /// Whether sharing a die counts as interference.
+    pub die: bool,
+}
+
+impl Interference {
+    /// Create a model.
+    pub fn new(config: &Config) -> Result<Interference> {
+        let model = Interference {
+            slowdown: *config.get::<f64>("slowdown").unwrap_or(&0.1),
+            exponent: *config.get::<f64>("exponent").unwrap_or(&1.0),
+            die: match config.get::<String>("scope") {
+                Some(scope) if scope == "die" => true,
+                Some(scope) if scope == "element" => false,
+                Some(scope) => raise!("found an unknown scope of interference ({:?})", scope),
+                _ => false,
+            },
+        };
+        if model.slowdown < 0.0 || model.exponent < 0.0 {
+            raise!("the parameters of interference should be nonnegative");
+        }
+        Ok(model)
+    }
+
+    /// Return the factors by which the speed and the dynamic power of a job
+    /// are scaled given its intensity and the pressure exerted on it.
+    #[inline]
+    pub fn factors(&self, intensity: f64, pressure: f64) -> (f64, f64) {
+        let speed = 1.0 / (1.0 + self.slowdown * intensity * pressure);
+        (speed, speed.powf(self.exponent))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Interference;
+
+    #[test]
+    fn factors() {
+        let model = Interference { slowdown: 0.5, exponent: 2.0, die: false };
+        assert_eq!(model.factors(1.0, 0.0), (1.0, 1.0));
+        assert_eq!(model.factors(1.0, 2.0), (0.5, 0.25));
+        assert_eq!(model.factors(0.0, 2.0), (1.0, 1.0));
+    }
+}
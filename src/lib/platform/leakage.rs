@@ -0,0 +1,284 @@
+use {Config, Result};
+use list;
+use platform::Element;
+use platform::element::matches;
+
+/// A temperature-dependent model of leakage power.
+pub struct Leakage {
+    power: Vec<f64>,
+    models: Vec<LeakageModel>,
+}
+
+/// The source of the nominal leakage power of processing elements.
+#[derive(Clone, Debug, PartialEq)]
+pub enum LeakageSource {
+    /// The component of the same type in the leakage pattern scaled by area.
+    Pattern,
+    /// The component with a given name in the leakage pattern scaled by area.
+    Component(String),
+    /// A constant power density.
+    Density(f64),
+    /// A constant power.
+    Power(f64),
+    /// No leakage power.
+    None,
+}
+
+/// An assignment of a leakage source to the processing elements whose names
+/// match certain patterns, optionally restricted to a die.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LeakageMapping {
+    /// The patterns of the names of the processing elements.
+    pub patterns: Vec<String>,
+    /// The index of the die if restricted.
+    pub die: Option<usize>,
+    /// The source of leakage power.
+    pub source: LeakageSource,
+}
+
+/// The temperature dependence of leakage power.
+#[derive(Clone, Debug, PartialEq)]
+pub enum LeakageModel {
+    /// No dependence.
+    Constant,
+    /// A linear dependence with respect to a reference temperature.
+    Linear {
+        /// The relative change per kelvin.
+        coefficient: f64,
+        /// The temperature at which the nominal power is attained.
+        reference: f64,
+    },
+    /// An exponential dependence with respect to a reference temperature.
+    Exponential {
+        /// The relative rate of change per kelvin.
+        coefficient: f64,
+        /// The temperature at which the nominal power is attained.
+        reference: f64,
+    },
+    /// A piecewise-linear dependence given by a table.
+    ///
+    /// The table is configured as an array of `points` tables with
+    /// `temperature` and `scale` keys.
+    Table {
+        /// The temperatures in ascending order.
+        temperature: Vec<f64>,
+        /// The scaling factors of the nominal power.
+        scale: Vec<f64>,
+    },
+}
+
+impl Leakage {
+    /// Create a model given the nominal leakage power of the processing
+    /// elements and an optional configuration with a section per element type.
+    pub fn new(elements: &[Element], power: Vec<f64>, config: Option<&Config>)
+               -> Result<Leakage> {
+        debug_assert_eq!(elements.len(), power.len());
+        let mut models = Vec::with_capacity(elements.len());
+        for element in elements {
+            let config = config.and_then(|config| config.branch(&element.kind.to_string()));
+            let model = match config {
+                Some(ref config) => try!(LeakageModel::new(config)),
+                _ => LeakageModel::Constant,
+            };
+            models.push(model);
+        }
+        Ok(Leakage { power: power, models: models })
+    }
+
+    /// Check if the leakage power is independent of temperature.
+    pub fn is_constant(&self) -> bool {
+        self.models.iter().all(|model| model == &LeakageModel::Constant)
+    }
+
+    /// Scale the nominal leakage power of the processing elements.
+    pub fn scale(&mut self, scale: &[f64]) {
+        debug_assert_eq!(scale.len(), self.power.len());
+        for (power, &scale) in self.power.iter_mut().zip(scale) {
+            *power *= scale;
+        }
+    }
+
+    /// Add the leakage power corresponding to a temperature to a power
+    /// vector, scaling the power of each processing element by a fraction.
+    pub fn apply(&self, temperature: &[f64], fraction: &[f64], power: &mut [f64]) {
+        debug_assert_eq!(temperature.len(), self.power.len());
+        debug_assert_eq!(fraction.len(), self.power.len());
+        debug_assert_eq!(power.len(), self.power.len());
+        for (i, model) in self.models.iter().enumerate() {
+            power[i] += fraction[i] * self.power[i] * model.scale(temperature[i]);
+        }
+    }
+}
+
+impl LeakageMapping {
+    /// Create a mapping.
+    ///
+    /// The patterns are given by `pattern` or by `patterns` as a
+    /// comma-separated string or an array of tables with a `pattern` key.
+    pub fn new(config: &Config) -> Result<LeakageMapping> {
+        let patterns = match (try!(list::read::<String>(config, "patterns", "pattern")),
+                              config.get::<String>("pattern")) {
+            (Some(ref patterns), _) if !patterns.is_empty() => {
+                patterns.iter().map(|pattern| pattern.to_lowercase()).collect()
+            },
+            (None, Some(pattern)) => vec![pattern.to_lowercase()],
+            _ => raise!("the patterns of a leakage mapping are required"),
+        };
+        let die = match config.get::<i64>("die") {
+            Some(&die) if die >= 0 => Some(die as usize),
+            Some(_) => raise!("the die of a leakage mapping should be nonnegative"),
+            _ => None,
+        };
+        let source = match (config.get::<f64>("power"), config.get::<f64>("density"),
+                            config.get::<String>("component"), config.get::<String>("source")) {
+            (Some(&power), _, _, _) if power >= 0.0 => LeakageSource::Power(power),
+            (_, Some(&density), _, _) if density >= 0.0 => LeakageSource::Density(density),
+            (_, _, Some(name), _) => LeakageSource::Component(name.to_lowercase()),
+            (_, _, _, Some(source)) if source == "pattern" => LeakageSource::Pattern,
+            (_, _, _, Some(source)) if source == "none" => LeakageSource::None,
+            _ => raise!("found an invalid leakage mapping for {:?}", patterns),
+        };
+        Ok(LeakageMapping { patterns: patterns, die: die, source: source })
+    }
+
+    /// Check if the mapping covers a processing element.
+    pub fn matches(&self, element: &Element) -> bool {
+        if self.die.map(|die| die != element.die).unwrap_or(false) {
+            return false;
+        }
+        let name = element.name.to_lowercase();
+        self.patterns.iter().any(|pattern| matches(pattern.as_bytes(), name.as_bytes()))
+    }
+}
+
+impl LeakageModel {
+    /// Create a model.
+    pub fn new(config: &Config) -> Result<LeakageModel> {
+        macro_rules! get(
+            ($name:expr) => (*some!(config.get::<f64>($name),
+                                    "the {} of a leakage model is required", $name));
+        );
+        let name = some!(config.get::<String>("model"),
+                         "the type of a leakage model is required");
+        Ok(match &name[..] {
+            "constant" => LeakageModel::Constant,
+            "linear" => LeakageModel::Linear {
+                coefficient: get!("coefficient"),
+                reference: get!("reference"),
+            },
+            "exponential" => LeakageModel::Exponential {
+                coefficient: get!("coefficient"),
+                reference: get!("reference"),
+            },
+            "table" => {
+                let configs = some!(config.forest("points"),
+                                    "the points of a leakage table are required");
+                let (mut temperature, mut scale) = (vec![], vec![]);
+                for config in &configs {
+                    temperature.push(*some!(config.get::<f64>("temperature"),
+                                            "the temperature of a leakage point is required"));
+                    scale.push(*some!(config.get::<f64>("scale"),
+                                      "the scaling factor of a leakage point is required"));
+                }
+                if temperature.is_empty() {
+                    raise!("found a malformed leakage table");
+                }
+                if temperature.windows(2).any(|pair| pair[0] >= pair[1]) {
+                    raise!("the temperatures of a leakage table should be ascending");
+                }
+                LeakageModel::Table { temperature: temperature, scale: scale }
+            },
+            _ => raise!("found an unknown leakage model ({:?})", name),
+        })
+    }
+
+    /// Compute the scaling factor of the nominal power at a temperature.
+    pub fn scale(&self, temperature: f64) -> f64 {
+        match self {
+            &LeakageModel::Constant => 1.0,
+            &LeakageModel::Linear { coefficient, reference } => {
+                (1.0 + coefficient * (temperature - reference)).max(0.0)
+            },
+            &LeakageModel::Exponential { coefficient, reference } => {
+                (coefficient * (temperature - reference)).exp()
+            },
+            &LeakageModel::Table { temperature: ref x, scale: ref y } => {
+                let n = x.len();
+                if temperature <= x[0] {
+                    return y[0];
+                }
+                if temperature >= x[n - 1] {
+                    return y[n - 1];
+                }
+                let i = x.iter().position(|&x| x > temperature).unwrap();
+                let w = (temperature - x[i - 1]) / (x[i] - x[i - 1]);
+                (1.0 - w) * y[i - 1] + w * y[i]
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use assert;
+    use configuration::format::TOML;
+    use platform::{Element, ElementCapacity, ElementKind};
+    use super::{LeakageMapping, LeakageModel, LeakageSource};
+
+    #[test]
+    fn matches() {
+        let core = ElementKind::new("core");
+        let elements = vec![
+            Element::new(0, "Core0".to_string(), core.clone(), ElementCapacity::Single, 1.0, 0),
+            Element::new(1, "Core1".to_string(), core.clone(), ElementCapacity::Single, 1.0, 1),
+        ];
+        let mapping = LeakageMapping {
+            patterns: vec!["core*".to_string()],
+            die: Some(1),
+            source: LeakageSource::Power(1.0),
+        };
+        assert!(!mapping.matches(&elements[0]) && mapping.matches(&elements[1]));
+        let mapping = LeakageMapping {
+            patterns: vec!["core0".to_string()],
+            die: None,
+            source: LeakageSource::None,
+        };
+        assert!(mapping.matches(&elements[0]) && !mapping.matches(&elements[1]));
+    }
+
+    #[test]
+    fn new() {
+        let config = TOML::parse(r#"
+            model = "table"
+
+            [[points]]
+            temperature = 300.0
+            scale = 1.0
+
+            [[points]]
+            temperature = 320.0
+            scale = 2.0
+        "#).unwrap();
+        assert_eq!(LeakageModel::new(&config).unwrap(), LeakageModel::Table {
+            temperature: vec![300.0, 320.0],
+            scale: vec![1.0, 2.0],
+        });
+    }
+
+    #[test]
+    fn scale() {
+        let model = LeakageModel::Linear { coefficient: 0.01, reference: 300.0 };
+        assert::close(&[model.scale(300.0), model.scale(350.0), model.scale(100.0)],
+                      &[1.0, 1.5, 0.0], 1e-14);
+
+        let model = LeakageModel::Exponential { coefficient: 0.02, reference: 300.0 };
+        assert::close(&[model.scale(300.0), model.scale(350.0)], &[1.0, 1f64.exp()], 1e-14);
+
+        let model = LeakageModel::Table {
+            temperature: vec![300.0, 320.0, 360.0],
+            scale: vec![1.0, 2.0, 4.0],
+        };
+        assert::close(&[model.scale(290.0), model.scale(310.0), model.scale(350.0),
+                        model.scale(370.0)], &[1.0, 1.5, 3.5, 4.0], 1e-14);
+    }
+}
@@ -4,13 +4,47 @@ use Result;
 use schedule::Mapping;
 use system::Job;
 
+mod compact;
+mod conditions;
+mod data;
+mod dtm;
 mod element;
+mod floorplan;
+mod generator;
+mod grid;
+mod idle;
+mod interference;
+mod leakage;
+mod power;
 mod profile;
+mod reliability;
+mod scaling;
+mod sensor;
+mod supply;
 mod thermal;
+mod variation;
 
-pub use self::element::{Element, ElementCapacity, ElementKind};
-pub use self::profile::{Profile, ProfileBuilder};
+pub use self::compact::{Compact, Layer};
+pub use self::conditions::Conditions;
+pub use self::data::{Data, Map};
+pub use self::dtm::{Dtm, DtmPolicy};
+pub use self::element::{Bounds, Element, ElementCapacity, ElementClass, ElementClassifier};
+pub use self::element::ElementKind;
+pub use self::element::PowerCombination;
+pub use self::floorplan::{Block, Floorplan};
+pub use self::generator::Generator;
+pub use self::grid::{Grid, Probe};
+pub use self::idle::{ElementState, Idle, IdlePolicy, IdleState};
+pub use self::interference::Interference;
+pub use self::leakage::{Leakage, LeakageMapping, LeakageModel, LeakageSource};
+pub use self::power::Power;
+pub use self::profile::{Profile, ProfileBuilder, Reduction};
+pub use self::reliability::{Reliability, Wear};
+pub use self::scaling::Scaling;
+pub use self::sensor::{Sensor, Sensors};
+pub use self::supply::{Network, Supply};
 pub use self::thermal::Thermal;
+pub use self::variation::Variation;
 
 /// A multiprocessor platform.
 pub trait Platform {
@@ -25,4 +59,56 @@ pub trait Platform {
 
     /// Account for a scheduling decision taken with respect to a job.
     fn push(&mut self, &Job, start: f64, mapping: &Mapping) -> Result<()>;
+
+    /// Return the process variation if modeled.
+    fn variation(&self) -> Option<&Variation> {
+        None
+    }
+
+    /// Return the reliability model if any.
+    fn reliability(&self) -> Option<&Reliability> {
+        None
+    }
+
+    /// Return the changes imposed by the platform since the previous call.
+    fn notices(&mut self) -> Vec<Notice> {
+        vec![]
+    }
+}
+
+/// A change imposed by a platform.
+#[derive(Clone, Debug)]
+pub enum Notice {
+    /// A processing element has been throttled.
+    ///
+    /// The speed is relative to the nominal one; it is one if the work of the
+    /// processing element has been migrated elsewhere.
+    Throttle {
+        /// The time.
+        time: f64,
+        /// The identifier of the processing element.
+        element: usize,
+        /// The speed.
+        speed: f64,
+    },
+    /// A processing element has been unthrottled.
+    Unthrottle {
+        /// The time.
+        time: f64,
+        /// The identifier of the processing element.
+        element: usize,
+    },
+    /// The execution of a job has changed.
+    ///
+    /// The start and finish are infinite if the job is suspended.
+    Reschedule {
+        /// The identifier of the job.
+        job: usize,
+        /// The start of the execution interval.
+        start: f64,
+        /// The end of the execution interval.
+        finish: f64,
+        /// The mapping of the job to the platform.
+        mapping: Mapping,
+    },
 }
@@ -0,0 +1,588 @@
+use std::collections::HashMap;
+use std::f64::INFINITY;
+use std::mem;
+use std::path::Path;
+
+use {Config, Result};
+use platform::{Bounds, Data, Element, ElementClassifier, Floorplan, Generator, Idle};
+use platform::{Interference, Leakage, LeakageMapping, LeakageSource, Notice, Platform};
+use platform::{PowerCombination, Profile, ProfileBuilder, Scaling, Supply};
+use schedule::Mapping;
+use system::Job;
+use workload::Component;
+
+mod plan;
+
+use self::plan::{Placement, Task};
+
+/// A platform producing power data.
+pub struct Power {
+    elements: Vec<Element>,
+    builder: ProfileBuilder,
+    occupancy: ProfileBuilder,
+    combination: Vec<PowerCombination>,
+    leakage: Leakage,
+    idle: Idle,
+    supply: Option<Supply>,
+    metrics: Vec<Metric>,
+    interference: Option<Interference>,
+    scaling: Scaling,
+    running: Vec<usize>,
+    temperature: Vec<f64>,
+    speed: Vec<f64>,
+    scale: Vec<f64>,
+    tasks: Vec<Task>,
+    notices: Vec<Notice>,
+}
+
+// A dynamic metric of workload components projected onto processing elements.
+// The weight counts the components contributing to a mean.
+struct Metric {
+    name: String,
+    builder: ProfileBuilder,
+    weight: Option<ProfileBuilder>,
+    pulled: Option<Profile>,
+}
+
+
+impl Power {
+    /// Create a platform.
+    ///
+    /// The processing elements are taken from the `elements` section or, if
+    /// absent, from the `generator` section or the floorplan given in the
+    /// `floorplan` section.
+    pub fn new(config: &Config, classifier: &ElementClassifier) -> Result<Power> {
+        macro_rules! branch(($name:expr) => (config.branch($name).as_ref().unwrap_or(config)));
+        let elements = try!(construct_elements(classifier, config));
+        info!(target: "Platform", "Found {} processing elements.", elements.len());
+        let mut platform = try!(Power::with_elements(elements, classifier, config));
+        match branch!("power").get::<f64>("ambience") {
+            Some(&ambience) => {
+                for temperature in &mut platform.temperature {
+                    *temperature = ambience;
+                }
+            },
+            _ => if !platform.leakage.is_constant() {
+                raise!("an ambient temperature is required for temperature-dependent leakage");
+            },
+        }
+        Ok(platform)
+    }
+
+    /// Create a platform given processing elements.
+    ///
+    /// The power model is configured in the `power` section, and the power
+    /// supply is modeled if the `supply` section is present (see `Supply`).
+    /// The dynamic metrics of workload components listed in `metrics` are
+    /// projected onto processing elements in addition to the dynamic power.
+    /// The values of the components running on the same processing element
+    /// are either summed up, which is suitable for rates, or averaged, which
+    /// is suitable for ratios, depending on `combination` (`sum` or `mean`).
+    ///
+    /// If the `interference` section is present, co-running jobs sharing
+    /// resources slow each other down (see `Interference`). The dynamic power
+    /// of workload patterns is adapted to the platform according to the
+    /// `scaling` section if present (see `Scaling`).
+    pub fn with_elements(elements: Vec<Element>, classifier: &ElementClassifier,
+                         config: &Config) -> Result<Power> {
+        let element_count = elements.len();
+        let branch = config.branch("power");
+        let (root, config) = (config, branch.as_ref().unwrap_or(config));
+        let time_step = *some!(config.get::<f64>("time_step"), "a time step is required");
+        let leakage = try!(construct_leakage(&elements, classifier, config));
+        let idle = try!(Idle::new(&elements, config.branch("idle").as_ref()));
+        let mut combination = Vec::with_capacity(element_count);
+        for element in &elements {
+            combination.push(some!(classifier.find(&element.kind)).combination);
+        }
+        let supply = match root.branch("supply") {
+            Some(ref config) => Some(try!(Supply::new(&elements, time_step, config))),
+            _ => None,
+        };
+        let interference = match config.branch("interference") {
+            Some(ref config) => Some(try!(Interference::new(config))),
+            _ => None,
+        };
+        let scaling = match config.branch("scaling") {
+            Some(ref config) => try!(Scaling::new(config)),
+            _ => Scaling::default(),
+        };
+        let mut metrics = vec![];
+        if let Some(ref configs) = config.forest("metrics") {
+            for config in configs {
+                metrics.push(try!(Metric::new(element_count, time_step, config)));
+            }
+        }
+        Ok(Power {
+            elements: elements,
+            builder: ProfileBuilder::new(element_count, time_step, vec![0.0; element_count]),
+            occupancy: ProfileBuilder::new(element_count, time_step, vec![0.0; element_count]),
+            combination: combination,
+            leakage: leakage,
+            idle: idle,
+            supply: supply,
+            metrics: metrics,
+            interference: interference,
+            scaling: scaling,
+            running: vec![],
+            temperature: vec![0.0; element_count],
+            speed: vec![1.0; element_count],
+            scale: vec![1.0; element_count],
+            tasks: vec![],
+            notices: vec![],
+        })
+    }
+
+    /// Return the beginning of the time interval that has not been pulled yet.
+    #[inline]
+    pub fn time(&self) -> f64 {
+        self.builder.time()
+    }
+
+    /// Return the time step (sampling interval).
+    #[inline]
+    pub fn time_step(&self) -> f64 {
+        self.builder.time_step()
+    }
+
+    /// Return the leakage model.
+    #[inline(always)]
+    pub fn leakage(&self) -> &Leakage {
+        &self.leakage
+    }
+
+    /// Scale the nominal leakage power of the processing elements.
+    pub fn vary(&mut self, scale: &[f64]) {
+        self.leakage.scale(scale);
+    }
+
+    /// Return the current and the voltage droop caused by a power profile if
+    /// the power supply is modeled.
+    pub fn supply(&mut self, power: &Profile) -> (Option<Profile>, Option<Profile>) {
+        match self.supply {
+            Some(ref mut supply) => {
+                let (current, droop) = supply.next(power);
+                (Some(current), Some(droop))
+            },
+            _ => (None, None),
+        }
+    }
+
+    /// Add the leakage power of a time step to a power vector given the
+    /// temperature and the states of the processing elements.
+    pub fn leak(&self, temperature: &[f64], state: &[f64], power: &mut [f64]) {
+        let fraction = state.iter().enumerate().map(|(i, &state)| self.idle.fraction(i, state))
+                                               .collect::<Vec<_>>();
+        self.leakage.apply(temperature, &fraction, power);
+    }
+
+    /// Advance time and return the dynamic and transition power accumulated
+    /// since the previous call along with the states of the processing
+    /// elements.
+    pub fn pull(&mut self, time: f64) -> (Profile, Profile) {
+        let time = time.max(self.time());
+        let power = self.builder.pull(time);
+        let occupancy = self.occupancy.pull(time);
+        for metric in &mut self.metrics {
+            metric.pull(time);
+        }
+        self.contend();
+        self.process(power, occupancy)
+    }
+
+    /// Advance time by one time step and return the corresponding dynamic and
+    /// transition power along with the states of the processing elements.
+    pub fn step(&mut self) -> (Profile, Profile) {
+        let power = self.builder.step();
+        let occupancy = self.occupancy.step();
+        for metric in &mut self.metrics {
+            metric.step();
+        }
+        self.contend();
+        self.process(power, occupancy)
+    }
+
+    /// Return the dynamic metrics pulled since the previous call.
+    pub fn metrics(&mut self) -> Vec<(String, Profile)> {
+        let (element_count, time_step) = (self.elements.len(), self.time_step());
+        self.metrics.iter_mut().map(|metric| {
+            let profile = metric.pulled.take().unwrap_or_else(|| {
+                let mut profile = Profile::new(element_count, time_step);
+                profile.time = metric.builder.time();
+                profile
+            });
+            (metric.name.clone(), profile)
+        }).collect()
+    }
+
+    // Replan if the set of running jobs has changed since the last time, which
+    // changes the interference between them.
+    fn contend(&mut self) {
+        if self.interference.is_none() {
+            return;
+        }
+        let time = self.time();
+        let running = self.tasks.iter().filter(|task| task.is_running(time))
+                                       .map(|task| task.job.id).collect::<Vec<_>>();
+        if running != self.running {
+            self.running = running;
+            self.replan();
+        }
+    }
+
+    fn place(&mut self, index: usize, sign: f64) {
+        let &mut Power {
+            ref elements, ref tasks, ref mut builder, ref mut occupancy, ref mut metrics,
+            ref scaling, ..
+        } = self;
+        let Task { ref job, ref mapping, ref placement, .. } = tasks[index];
+        if !(placement.speed > 0.0) || !placement.anchor.is_finite() {
+            return;
+        }
+        let time = placement.anchor - placement.progress / placement.speed;
+        let time_step = job.time_step / placement.speed;
+        for (k, &(i, j)) in mapping.iter().enumerate() {
+            let scale = sign * placement.scale[k] *
+                        scaling.factor(&job.components[i], &elements[j]);
+            let data = job.components[i].dynamic_power.iter().map(|&power| scale * power)
+                                                              .collect::<Vec<_>>();
+            builder.push(j, time, time_step, &data);
+            occupancy.push(j, time, time_step, &vec![sign; data.len()]);
+            for metric in metrics.iter_mut() {
+                if let Some(data) = job.components[i].metrics.get(&metric.name) {
+                    metric.push(j, time, time_step, sign, data);
+                }
+            }
+        }
+    }
+
+    fn process(&mut self, mut power: Profile, occupancy: Profile) -> (Profile, Profile) {
+        let (element_count, step_count) = (power.element_count, power.step_count);
+        for (j, combination) in self.combination.iter().enumerate() {
+            if let &PowerCombination::Additive = combination {
+                continue;
+            }
+            for i in 0..step_count {
+                let k = i * element_count + j;
+                power[k] *= combination.scale(occupancy[k]);
+            }
+        }
+        let state = self.idle.next(&occupancy, &mut power);
+        (power, state)
+    }
+}
+
+impl Platform for Power {
+    type Data = Data;
+
+    #[inline(always)]
+    fn elements(&self) -> &[Element] {
+        &self.elements
+    }
+
+    fn next(&mut self, time: f64) -> Result<Self::Data> {
+        let (mut power, state) = self.pull(time);
+        let element_count = power.element_count;
+        for i in 0..power.step_count {
+            let range = (i * element_count)..((i + 1) * element_count);
+            self.leak(&self.temperature, &state[range.clone()], &mut power[range]);
+        }
+        let (current, droop) = self.supply(&power);
+        Ok(Data {
+            power: power,
+            state: state,
+            temperature: None,
+            sensed: None,
+            peak: None,
+            probes: None,
+            map: None,
+            damage: None,
+            current: current,
+            droop: droop,
+            metrics: self.metrics(),
+        })
+    }
+
+    fn push(&mut self, job: &Job, start: f64, mapping: &Mapping) -> Result<()> {
+        let mut placement = Placement {
+            start: start,
+            anchor: start,
+            progress: 0.0,
+            speed: INFINITY,
+            scale: Vec::with_capacity(mapping.len()),
+        };
+        for &(i, j) in mapping {
+            let (speed, scale) = job.components[i].factors(&self.elements[j]);
+            placement.speed = placement.speed.min(speed);
+            placement.scale.push(scale);
+        }
+        self.tasks.push(Task {
+            job: job.clone(),
+            mapping: mapping.clone(),
+            reserved: start,
+            placement: placement,
+        });
+        let index = self.tasks.len() - 1;
+        self.place(index, 1.0);
+        self.replan();
+        Ok(())
+    }
+
+    #[inline]
+    fn notices(&mut self) -> Vec<Notice> {
+        mem::replace(&mut self.notices, vec![])
+    }
+}
+
+impl Metric {
+    fn new(element_count: usize, time_step: f64, config: &Config) -> Result<Metric> {
+        let name = some!(config.get::<String>("name"), "the name of a metric is required");
+        let builder = || ProfileBuilder::new(element_count, time_step, vec![0.0; element_count]);
+        let weight = match config.get::<String>("combination").map(|name| &name[..]) {
+            Some("mean") => Some(builder()),
+            Some("sum") | None => None,
+            Some(combination) => {
+                raise!("found an unknown combination of metrics ({:?})", combination)
+            },
+        };
+        Ok(Metric { name: name.clone(), builder: builder(), weight: weight, pulled: None })
+    }
+
+    fn push(&mut self, id: usize, time: f64, time_step: f64, sign: f64, data: &[f64]) {
+        let data = data.iter().map(|&value| sign * value).collect::<Vec<_>>();
+        self.builder.push(id, time, time_step, &data);
+        if let Some(ref mut weight) = self.weight {
+            weight.push(id, time, time_step, &vec![sign; data.len()]);
+        }
+    }
+
+    fn pull(&mut self, time: f64) {
+        let profile = self.builder.pull(time);
+        let weight = self.weight.as_mut().map(|weight| weight.pull(time));
+        self.accept(profile, weight);
+    }
+
+    fn step(&mut self) {
+        let profile = self.builder.step();
+        let weight = self.weight.as_mut().map(|weight| weight.step());
+        self.accept(profile, weight);
+    }
+
+    fn accept(&mut self, mut profile: Profile, weight: Option<Profile>) {
+        if let Some(weight) = weight {
+            for (value, &weight) in profile.iter_mut().zip(weight.iter()) {
+                *value = if weight > 0.0 { *value / weight } else { 0.0 };
+            }
+        }
+        if let Some(ref mut pulled) = self.pulled {
+            pulled.data.extend_from_slice(&profile.data);
+            pulled.step_count += profile.step_count;
+            return;
+        }
+        self.pulled = Some(profile);
+    }
+}
+
+fn construct_elements(classifier: &ElementClassifier, config: &Config) -> Result<Vec<Element>> {
+    if let Some(ref configs) = config.forest("elements") {
+        let mut elements = vec![];
+        for config in configs {
+            let id = elements.len();
+            let name = some!(config.get::<String>("name"), "the name of an element is required");
+            let area = *some!(config.get::<f64>("area"), "the area of an element is required");
+            let die = config.get::<i64>("die").map(|&die| die as usize).unwrap_or(0);
+            if area <= 0.0 {
+                raise!("the area of an element should be positive");
+            }
+            let mut element = try!(classifier.element(id, name, area, die));
+            if let Some(&width) = config.get::<f64>("width") {
+                element.bounds = Some(Bounds {
+                    left: *config.get::<f64>("left").unwrap_or(&0.0),
+                    bottom: *config.get::<f64>("bottom").unwrap_or(&0.0),
+                    width: width,
+                    height: *some!(config.get::<f64>("height"),
+                                   "the height of an element is required"),
+                });
+            }
+            elements.push(element);
+        }
+        return Ok(elements);
+    }
+    if let Some(ref config) = config.branch("generator") {
+        return try!(Generator::new(config)).floorplan().elements(0, classifier);
+    }
+    let config = some!(config.branch("floorplan"),
+                       "a floorplan, a generator, or a list of processing elements is required");
+    let path = path!(config, "a floorplan is required");
+    info!(target: "Platform", "Reading the floorplan from {:?}...", &path);
+    try!(Floorplan::open(&path)).elements(0, classifier)
+}
+
+fn construct_leakage(elements: &[Element], classifier: &ElementClassifier, config: &Config)
+                     -> Result<Leakage> {
+    let mut mappings = vec![];
+    if let Some(ref configs) = config.forest("leakage_mappings") {
+        for config in configs {
+            mappings.push(try!(LeakageMapping::new(config)));
+        }
+    }
+    let table = match config.branch("leakage_table") {
+        Some(ref config) => {
+            let path = path!(config, "a leakage table is required");
+            info!(target: "Platform", "Reading leakage power from {:?}...", &path);
+            try!(read_leakage_table(&path))
+        },
+        _ => HashMap::new(),
+    };
+    let mut components = None;
+    let mut leakage_power = vec![0.0; elements.len()];
+    for (i, element) in elements.iter().enumerate() {
+        let source = match mappings.iter().find(|mapping| mapping.matches(element)) {
+            Some(mapping) => mapping.source.clone(),
+            _ => match table.get(&element.name.to_lowercase()) {
+                Some(&power) => LeakageSource::Power(power),
+                _ => some!(classifier.find(&element.kind)).leakage.clone(),
+            },
+        };
+        leakage_power[i] = match source {
+            LeakageSource::Pattern | LeakageSource::Component(_) => {
+                if components.is_none() {
+                    let path = path!(config, "a leakage pattern is required");
+                    info!(target: "Platform", "Modeling leakage power based on {:?}...", &path);
+                    components = Some(try!(Component::collect(path, classifier, &[])));
+                }
+                let mut components = components.as_ref().unwrap().iter();
+                let component = match source {
+                    LeakageSource::Component(ref name) => {
+                        components.find(|component| &component.name.to_lowercase() == name)
+                    },
+                    _ => components.clone().find(|component| component.kind == element.kind)
+                                   .or_else(|| {
+                        components.find(|component| component.family == element.family)
+                    }),
+                };
+                match component {
+                    Some(component) => {
+                        debug_assert!(element.area > 0.0 && component.area > 0.0);
+                        (element.area / component.area) * component.leakage_power
+                    },
+                    _ => raise!("cannot find leakage data for a processing element ({:?})",
+                                element.name),
+                }
+            },
+            LeakageSource::Density(density) => density * element.area,
+            LeakageSource::Power(power) => power,
+            LeakageSource::None => 0.0,
+        };
+    }
+    Leakage::new(elements, leakage_power, config.branch("leakage").as_ref())
+}
+
+fn read_leakage_table(path: &Path) -> Result<HashMap<String, f64>> {
+    use sql::prelude::*;
+    use sqlite::Connection;
+
+    let backend = ok!(Connection::open(path));
+    let mut data = HashMap::new();
+    let statement = select_from("leakage").columns(&["name", "leakage_power"]);
+    let mut cursor = ok!(backend.prepare(ok!(statement.compile()))).cursor();
+    while let Some(row) = ok!(cursor.next()) {
+        match (row[0].as_string(), row[1].as_float()) {
+            (Some(name), Some(power)) if power >= 0.0 => {
+                data.insert(name.to_lowercase(), power);
+            },
+            _ => raise!("found a malformed row in the leakage table"),
+        }
+    }
+    Ok(data)
+}
+
+#[cfg(test)]
+mod tests {
+    use configuration::format::TOML;
+    use platform::{ElementClassifier, Platform, Power};
+    use system::Job;
+    use workload::Pattern;
+
+    #[test]
+    fn leakage() {
+        let config = TOML::parse(r#"
+            root = "tests/fixtures"
+
+            [[kinds]]
+            name = "l3"
+            leakage = 2.0
+
+            [leakage_table]
+            path = "leakage.sqlite3"
+
+            [[leakage_mappings]]
+            patterns = "core0, core9"
+            power = 1.0
+        "#).unwrap();
+        let classifier = ElementClassifier::new(&config).unwrap();
+        let elements = vec![
+            classifier.element(0, "Core0", 1.0, 0).unwrap(),
+            classifier.element(1, "Core1", 1.0, 0).unwrap(),
+            classifier.element(2, "L30", 1.5, 0).unwrap(),
+        ];
+        let leakage = super::construct_leakage(&elements, &classifier, &config).unwrap();
+        let mut power = vec![0.0; 3];
+        leakage.apply(&[0.0; 3], &[1.0; 3], &mut power);
+        assert_eq!(power, vec![1.0, 2.0, 3.0]);
+    }
+
+    #[test]
+    fn metrics() {
+        let config = TOML::parse(r#"
+            root = "tests/fixtures"
+            time_step = 1e-3
+
+            [platform.floorplan]
+            path = "004_hotspot.flp"
+
+            [platform.power]
+            path = "metrics.sqlite3"
+
+            [[platform.power.metrics]]
+            name = "misses"
+
+            [[platform.power.metrics]]
+            name = "hit_rate"
+            combination = "mean"
+
+            [pattern]
+            path = "metrics.sqlite3"
+            metrics = "misses, hit_rate"
+        "#).unwrap();
+        let classifier = ElementClassifier::new(&config).unwrap();
+        let pattern = Pattern::new(&config.branch("pattern").unwrap(), &classifier).unwrap();
+        let mut platform = Power::new(&config.branch("platform").unwrap(), &classifier).unwrap();
+
+        let (one, other) = (Job::new(0, 0.0, pattern.clone()), Job::new(1, 0.0, pattern));
+        platform.push(&one, 0.0, &vec![(0, 0), (1, 4)]).unwrap();
+        platform.push(&other, 0.0, &vec![(0, 1), (1, 4)]).unwrap();
+
+        let data = platform.next(2e-3).unwrap();
+        assert_eq!(&data.metrics[0].0, "misses");
+        assert_eq!(&data.metrics[0].1[..], &[100.0, 100.0, 0.0, 0.0, 20.0,
+                                              200.0, 200.0, 0.0, 0.0, 40.0]);
+        assert_eq!(&data.metrics[1].0, "hit_rate");
+        assert_eq!(&data.metrics[1].1[..], &[0.9, 0.9, 0.0, 0.0, 0.25,
+                                              0.8, 0.8, 0.0, 0.0, 0.75]);
+    }
+
+    #[test]
+    fn new() {
+        let config = TOML::open("tests/fixtures/power.toml").unwrap();
+        let classifier = ElementClassifier::new(&config).unwrap();
+        let mut platform = Power::new(&config.branch("platform").unwrap(), &classifier).unwrap();
+        assert_eq!(platform.elements().len(), 5);
+        let data = platform.next(1e-2).unwrap();
+        assert_eq!(data.power.step_count, 10);
+        assert!(data.power.iter().all(|&power| power > 0.0));
+        assert_eq!(&data.state[..5], &[0.0, 0.0, 0.0, 0.0, 0.0]);
+        assert_eq!(&data.state[10..15], &[1.0, 1.0, 1.0, 1.0, 0.0]);
+        assert!(data.temperature.is_none());
+    }
+}
@@ -0,0 +1,419 @@
+use std::f64::INFINITY;
+
+use platform::{ElementCapacity, Interference, Notice};
+use schedule::Mapping;
+use system::Job;
+
+use super::Power;
+
+// A job known to the platform. The reserved start is the one taken by the
+// scheduler, and the placement is the actual execution given the current
+// state of the processing elements.
+pub struct Task {
+    pub job: Job,
+    pub mapping: Mapping,
+    pub reserved: f64,
+    pub placement: Placement,
+}
+
+// The execution of a job. The progress is the amount of work done by the time
+// of the anchor, and the work is done at the speed from then on.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Placement {
+    pub start: f64,
+    pub anchor: f64,
+    pub progress: f64,
+    pub speed: f64,
+    pub scale: Vec<f64>,
+}
+
+impl Power {
+    /// Change the speed and the relative dynamic power of a processing
+    /// element.
+    ///
+    /// The change takes effect once `replan` is called.
+    pub fn throttle(&mut self, id: usize, speed: f64, scale: f64) {
+        self.speed[id] = speed;
+        self.scale[id] = scale;
+    }
+
+    /// Move the running jobs of a processing element to other processing
+    /// elements of the same type that have no other work until the jobs
+    /// finish. The candidates are tried in the given order. The function
+    /// returns `false` if some of the jobs could not be moved.
+    pub fn migrate(&mut self, id: usize, candidates: &[usize]) -> bool {
+        let time = self.time();
+        let mut success = true;
+        for index in 0..self.tasks.len() {
+            let (start, finish) = self.tasks[index].interval();
+            if start > time || !self.tasks[index].mapping.iter().any(|&(_, j)| j == id) {
+                continue;
+            }
+            let found = candidates.iter().cloned().find(|&j| {
+                j != id && self.elements[j].kind == self.elements[id].kind &&
+                self.tasks.iter().all(|task| {
+                    let (other_start, other_finish) = task.interval();
+                    !task.mapping.iter().any(|&(_, k)| k == j) ||
+                    other_finish <= time || other_start >= finish
+                })
+            });
+            let target = match found {
+                Some(target) => target,
+                _ => {
+                    success = false;
+                    continue;
+                },
+            };
+            self.place(index, -1.0);
+            for pair in &mut self.tasks[index].mapping {
+                if pair.1 == id {
+                    pair.1 = target;
+                }
+            }
+            self.place(index, 1.0);
+            let task = &self.tasks[index];
+            self.notices.push(Notice::Reschedule {
+                job: task.job.id,
+                start: start,
+                finish: finish,
+                mapping: task.mapping.clone(),
+            });
+        }
+        success
+    }
+
+    /// Recompute the execution of the known jobs given the current speeds of
+    /// the processing elements.
+    ///
+    /// A job progresses at the speed of its slowest processing element, which
+    /// accounts for the relative performance of the element’s type and is
+    /// further reduced by interference with other running jobs if modeled.
+    /// The jobs that have not started yet are postponed until a slot is free
+    /// on each processing element of limited capacity they are mapped to, so
+    /// that an element of capacity `n` never runs more than `n` jobs at a time.
+    pub fn replan(&mut self) {
+        let time = self.time();
+        self.tasks.retain(|task| task.interval().1 > time);
+        self.tasks.sort_by(|one, other| {
+            (one.placement.start > time).cmp(&(other.placement.start > time))
+                                        .then(one.reserved.partial_cmp(&other.reserved).unwrap())
+                                        .then(one.job.id.cmp(&other.job.id))
+        });
+        let mut slots = self.elements.iter().map(|element| match element.capacity {
+            ElementCapacity::Single => vec![time],
+            ElementCapacity::Finite(count) => vec![time; count],
+            _ => vec![],
+        }).collect::<Vec<_>>();
+        for index in 0..self.tasks.len() {
+            let placement = {
+                let task = &self.tasks[index];
+                let (mut speed, mut scale) = self.factors(task);
+                let current = &task.placement;
+                if current.start <= time {
+                    let (factor, power) = self.interfere(index, time);
+                    speed *= factor;
+                    for value in &mut scale {
+                        *value *= power;
+                    }
+                    if current.speed == speed && current.scale == scale {
+                        current.clone()
+                    } else {
+                        Placement {
+                            start: current.start,
+                            anchor: time,
+                            progress: current.progress(time),
+                            speed: speed,
+                            scale: scale,
+                        }
+                    }
+                } else {
+                    let mut start = task.reserved.max(time);
+                    for &(_, j) in &task.mapping {
+                        if let Some(k) = vacant(&slots[j]) {
+                            start = start.max(slots[j][k]);
+                        }
+                    }
+                    Placement { start: start, anchor: start, progress: 0.0, speed: speed,
+                                scale: scale }
+                }
+            };
+            if placement != self.tasks[index].placement {
+                self.place(index, -1.0);
+                self.tasks[index].placement = placement;
+                self.place(index, 1.0);
+                let task = &self.tasks[index];
+                let (start, finish) = task.interval();
+                self.notices.push(Notice::Reschedule {
+                    job: task.job.id,
+                    start: start,
+                    finish: finish,
+                    mapping: task.mapping.clone(),
+                });
+            }
+            let finish = self.tasks[index].interval().1;
+            for &(_, j) in &self.tasks[index].mapping {
+                if let Some(k) = vacant(&slots[j]) {
+                    slots[j][k] = slots[j][k].max(finish);
+                }
+            }
+        }
+    }
+
+    // Return the speed of a task and the factors scaling the dynamic power of
+    // its components given the types and the current state of the processing
+    // elements.
+    fn factors(&self, task: &Task) -> (f64, Vec<f64>) {
+        let mut speed = INFINITY;
+        let mut scale = Vec::with_capacity(task.mapping.len());
+        for &(i, j) in &task.mapping {
+            let (one, other) = task.job.components[i].factors(&self.elements[j]);
+            speed = speed.min(one * self.speed[j]);
+            scale.push(other * self.scale[j]);
+        }
+        (speed, scale)
+    }
+
+    // Return the factors by which the speed and the dynamic power of a running
+    // task are scaled due to interference with the other running tasks.
+    fn interfere(&self, index: usize, time: f64) -> (f64, f64) {
+        let model = match self.interference {
+            Some(ref model) => model,
+            _ => return (1.0, 1.0),
+        };
+        let task = &self.tasks[index];
+        let pressure = self.tasks.iter().enumerate().filter(|&(other, _)| other != index)
+                                                    .map(|(_, other)| other)
+                                                    .filter(|other| other.placement.start <= time)
+                                                    .filter(|other| self.share(model, task, other))
+                                                    .fold(0.0, |sum, other| {
+            sum + other.job.intensity
+        });
+        model.factors(task.job.intensity, pressure)
+    }
+
+    fn share(&self, model: &Interference, one: &Task, other: &Task) -> bool {
+        one.mapping.iter().any(|&(_, i)| other.mapping.iter().any(|&(_, j)| {
+            if i == j {
+                self.elements[i].capacity != ElementCapacity::Single
+            } else {
+                model.die && self.elements[i].die == self.elements[j].die
+            }
+        }))
+    }
+}
+
+impl Task {
+    #[inline]
+    pub fn is_running(&self, time: f64) -> bool {
+        let (start, finish) = self.interval();
+        start <= time && time < finish
+    }
+
+    pub fn interval(&self) -> (f64, f64) {
+        let &Placement { start, anchor, progress, speed, .. } = &self.placement;
+        if speed > 0.0 && anchor.is_finite() {
+            (start, anchor + (self.job.duration() - progress) / speed)
+        } else {
+            (start, INFINITY)
+        }
+    }
+}
+
+impl Placement {
+    fn progress(&self, time: f64) -> f64 {
+        if self.speed > 0.0 && self.anchor < time {
+            self.progress + (time - self.anchor) * self.speed
+        } else {
+            self.progress
+        }
+    }
+}
+
+// Return the slot of a processing element that becomes free the earliest.
+fn vacant(slots: &[f64]) -> Option<usize> {
+    (0..slots.len()).fold(None, |best, k| match best {
+        Some(best) if slots[best] <= slots[k] => Some(best),
+        _ => Some(k),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use assert;
+    use configuration::format::TOML;
+    use platform::{ElementCapacity, ElementClassifier, Notice, Platform, Power};
+    use system::Job;
+    use workload::Pattern;
+
+    #[test]
+    fn finite() {
+        let config = TOML::parse(r#"
+            root = "tests/fixtures"
+            time_step = 1e-3
+
+            [[kinds]]
+            name = "core"
+
+            [[kinds]]
+            name = "l3"
+            capacity = 2
+
+            [platform.floorplan]
+            path = "004_hotspot.flp"
+
+            [platform.power]
+            path = "blackscholes.sqlite3"
+
+            [pattern]
+            path = "blackscholes.sqlite3"
+        "#).unwrap();
+        let classifier = ElementClassifier::new(&config).unwrap();
+        let pattern = Pattern::new(&config.branch("pattern").unwrap(), &classifier).unwrap();
+        let mut platform = Power::new(&config.branch("platform").unwrap(), &classifier).unwrap();
+        assert_eq!(platform.elements[4].capacity, ElementCapacity::Finite(2));
+
+        let jobs = (0..3).map(|i| Job::new(i, 0.0, pattern.clone())).collect::<Vec<_>>();
+        let duration = jobs[0].duration();
+        platform.push(&jobs[0], 0.0, &vec![(0, 0), (2, 4)]).unwrap();
+        platform.push(&jobs[1], 0.0, &vec![(0, 1), (2, 4)]).unwrap();
+        platform.push(&jobs[2], duration, &vec![(0, 2), (2, 4)]).unwrap();
+        assert!(platform.notices().is_empty());
+
+        platform.pull(1e-2);
+        platform.throttle(4, 0.5, 0.125);
+        platform.replan();
+        let time = platform.time();
+        let finish = time + (duration - time) / 0.5;
+        let notices = platform.notices();
+        assert_eq!(notices.len(), 3);
+        for notice in notices {
+            match notice {
+                Notice::Reschedule { job: 2, start, .. } => assert_eq!(start, finish),
+                Notice::Reschedule { finish: other, .. } => assert_eq!(other, finish),
+                _ => unreachable!(),
+            }
+        }
+    }
+
+    #[test]
+    fn heterogeneity() {
+        let config = TOML::open("tests/fixtures/power.toml").unwrap();
+        let classifier = ElementClassifier::new(&config).unwrap();
+        let pattern = {
+            let configs = config.branch("workload").unwrap().forest("patterns").unwrap();
+            Pattern::new(&configs[0], &classifier).unwrap()
+        };
+        let mut platform = Power::new(&config.branch("platform").unwrap(), &classifier).unwrap();
+        platform.elements[0].performance = 2.0;
+        platform.elements[0].power = 1.5;
+
+        let job = Job::new(0, 0.0, pattern);
+        let duration = job.duration();
+        platform.push(&job, 0.0, &vec![(0, 0)]).unwrap();
+        assert!(platform.notices().is_empty());
+        assert_eq!(platform.tasks[0].interval(), (0.0, duration / 2.0));
+        assert_eq!(platform.tasks[0].placement.scale, vec![1.5]);
+
+        platform.pull(1e-2);
+        platform.throttle(0, 0.5, 0.125);
+        platform.replan();
+        let time = platform.time();
+        match &platform.notices()[..] {
+            &[Notice::Reschedule { job: 0, finish, .. }] => {
+                assert_eq!(finish, time + (duration - time * 2.0) / 1.0);
+            },
+            _ => unreachable!(),
+        }
+        assert_eq!(platform.tasks[0].placement.scale, vec![1.5 * 0.125]);
+    }
+
+    #[test]
+    fn interference() {
+        let config = TOML::parse(r#"
+            root = "tests/fixtures"
+            time_step = 1e-3
+
+            [platform.floorplan]
+            path = "004_hotspot.flp"
+
+            [platform.power]
+            path = "blackscholes.sqlite3"
+
+            [platform.power.interference]
+            slowdown = 0.5
+
+            [pattern]
+            path = "blackscholes.sqlite3"
+        "#).unwrap();
+        let classifier = ElementClassifier::new(&config).unwrap();
+        let pattern = Pattern::new(&config.branch("pattern").unwrap(), &classifier).unwrap();
+        let mut platform = Power::new(&config.branch("platform").unwrap(), &classifier).unwrap();
+        assert_eq!(platform.elements[4].capacity, ElementCapacity::Infinite);
+
+        let (one, other) = (Job::new(0, 0.0, pattern.clone()), Job::new(1, 1e-2, pattern));
+        let duration = one.duration();
+        platform.push(&one, 0.0, &vec![(0, 0), (1, 1), (2, 4)]).unwrap();
+        assert!(platform.notices().is_empty());
+
+        platform.pull(1e-2);
+        let time = platform.time();
+        platform.push(&other, time, &vec![(0, 2), (1, 3), (2, 4)]).unwrap();
+        let notices = platform.notices();
+        assert_eq!(notices.len(), 2);
+        for notice in notices {
+            match notice {
+                Notice::Reschedule { job: 0, finish, .. } => {
+                    assert!(finish > duration);
+                    assert::close(&[finish], &[time + (duration - time) * (1.0 + 0.5)], 1e-12);
+                },
+                Notice::Reschedule { job: 1, finish, .. } => {
+                    assert::close(&[finish], &[time + duration * (1.0 + 0.5)], 1e-12);
+                },
+                _ => unreachable!(),
+            }
+        }
+    }
+
+    #[test]
+    fn replan() {
+        let config = TOML::open("tests/fixtures/power.toml").unwrap();
+        let classifier = ElementClassifier::new(&config).unwrap();
+        let pattern = {
+            let configs = config.branch("workload").unwrap().forest("patterns").unwrap();
+            Pattern::new(&configs[0], &classifier).unwrap()
+        };
+        let mut platform = Power::new(&config.branch("platform").unwrap(), &classifier).unwrap();
+
+        let (one, other) = (Job::new(0, 0.0, pattern.clone()), Job::new(1, 0.0, pattern));
+        let duration = one.duration();
+        let mapping = vec![(0, 0)];
+        platform.push(&one, 0.0, &mapping).unwrap();
+        platform.push(&other, duration, &mapping).unwrap();
+        assert!(platform.notices().is_empty());
+
+        platform.pull(1e-2);
+        platform.throttle(0, 0.5, 0.125);
+        platform.replan();
+        let time = platform.time();
+        let finish = time + (duration - time) / 0.5;
+        match &platform.notices()[..] {
+            &[Notice::Reschedule { job: 0, start: start1, finish: finish1, .. },
+              Notice::Reschedule { job: 1, start: start2, finish: finish2, .. }] => {
+                assert_eq!((start1, finish1), (0.0, finish));
+                assert_eq!((start2, finish2), (finish, finish + duration / 0.5));
+            },
+            _ => unreachable!(),
+        }
+
+        platform.throttle(0, 0.0, 0.0);
+        platform.replan();
+        let notices = platform.notices();
+        assert_eq!(notices.len(), 2);
+        for notice in notices {
+            match notice {
+                Notice::Reschedule { finish, .. } => assert!(finish.is_infinite()),
+                _ => unreachable!(),
+            }
+        }
+    }
+}
@@ -1,9 +1,14 @@
-use std::mem;
+use std::collections::VecDeque;
+
+use Result;
 
 /// A platform profile.
 ///
 /// A profile is a matrix that captures the evolution of a parameter over a time
-/// interval with respect to a number of processing elements.
+/// interval with respect to a number of processing elements. The data are
+/// stored step by step, that is, the values of all processing elements at the
+/// first time step are followed by those at the second one and so on.
+#[derive(Clone, Debug)]
 pub struct Profile {
     /// The number of processing elements.
     pub element_count: usize,
@@ -17,9 +22,25 @@ pub struct Profile {
     pub data: Vec<f64>,
 }
 
+/// A reduction of a number of values to one.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Reduction {
+    /// The arithmetic mean.
+    Mean,
+    /// The maximum.
+    Max,
+}
+
 /// A builder of platform profiles.
+///
+/// The data that have not been pulled yet are kept in a ring buffer so that
+/// pushing and pulling take time proportional to the data touched.
 pub struct ProfileBuilder {
-    profile: Profile,
+    element_count: usize,
+    step_count: usize,
+    time: f64,
+    time_step: f64,
+    data: VecDeque<f64>,
     fill: Vec<f64>,
 }
 
@@ -47,13 +68,151 @@ impl Profile {
         }
     }
 
-    fn extend(&mut self, step_count: usize, fill: &[f64]) {
-        debug_assert_eq!(fill.len(), self.element_count);
-        self.data.reserve(step_count * self.element_count);
-        for _ in 0..step_count {
-            self.data.extend(fill);
+    /// Return the end of the time interval.
+    #[inline]
+    pub fn end(&self) -> f64 {
+        self.time + (self.step_count as f64) * self.time_step
+    }
+
+    /// Return the data of a processing element.
+    pub fn element(&self, id: usize) -> Vec<f64> {
+        debug_assert!(id < self.element_count);
+        (0..self.step_count).map(|i| self.data[i * self.element_count + id]).collect()
+    }
+
+    /// Return the data of a time step.
+    #[inline]
+    pub fn step(&self, i: usize) -> &[f64] {
+        &self.data[(i * self.element_count)..((i + 1) * self.element_count)]
+    }
+
+    /// Resample the profile to a coarser time step, which should be a
+    /// multiple of the current one.
+    ///
+    /// The last time step of the result covers the remaining data if they are
+    /// not enough to fill it in completely.
+    pub fn downsample(&self, time_step: f64, reduction: Reduction) -> Result<Profile> {
+        let factor = (time_step / self.time_step).round();
+        if factor < 1.0 || (factor * self.time_step - time_step).abs() > 1e-9 * time_step {
+            raise!("the time step should be a multiple of the one of the profile");
         }
-        self.step_count += step_count;
+        let (element_count, factor) = (self.element_count, factor as usize);
+        let step_count = (self.step_count + factor - 1) / factor;
+        let mut data = Vec::with_capacity(step_count * element_count);
+        for i in 0..step_count {
+            let steps = (i * factor)..::std::cmp::min((i + 1) * factor, self.step_count);
+            let count = (steps.end - steps.start) as f64;
+            let offset = data.len();
+            data.extend_from_slice(self.step(steps.start));
+            for j in (steps.start + 1)..steps.end {
+                for (value, &other) in data[offset..].iter_mut().zip(self.step(j)) {
+                    match reduction {
+                        Reduction::Mean => *value += other,
+                        Reduction::Max => *value = value.max(other),
+                    }
+                }
+            }
+            if let Reduction::Mean = reduction {
+                for value in &mut data[offset..] {
+                    *value /= count;
+                }
+            }
+        }
+        Ok(Profile {
+            element_count: element_count,
+            step_count: step_count,
+            time: self.time,
+            time_step: time_step,
+            data: data,
+        })
+    }
+
+    /// Append a profile that starts where the current one ends.
+    pub fn append(&mut self, other: &Profile) -> Result<()> {
+        if self.element_count != other.element_count ||
+           (self.time_step - other.time_step).abs() > 1e-9 * self.time_step {
+            raise!("the profiles should have the same elements and time step");
+        }
+        if (self.end() - other.time).abs() > 1e-9 * self.time_step {
+            raise!("the profiles should be consecutive");
+        }
+        self.data.extend_from_slice(&other.data);
+        self.step_count += other.step_count;
+        Ok(())
+    }
+
+    /// Compute the integral over time of each processing element, which is
+    /// the energy in the case of power.
+    pub fn energy(&self) -> Vec<f64> {
+        let mut energy = self.sum();
+        for value in &mut energy {
+            *value *= self.time_step;
+        }
+        energy
+    }
+
+    /// Compute the mean of each processing element.
+    pub fn mean(&self) -> Vec<f64> {
+        let mut mean = self.sum();
+        for value in &mut mean {
+            *value /= self.step_count as f64;
+        }
+        mean
+    }
+
+    /// Compute the maximum of each processing element.
+    pub fn peak(&self) -> Vec<f64> {
+        let mut peak = vec![::std::f64::NEG_INFINITY; self.element_count];
+        for i in 0..self.step_count {
+            for (value, &other) in peak.iter_mut().zip(self.step(i)) {
+                *value = value.max(other);
+            }
+        }
+        peak
+    }
+
+    /// Compute a percentile (from 0 to 100) of each processing element.
+    ///
+    /// The values between two data points are interpolated linearly. NaN
+    /// values are ordered after all the other values.
+    pub fn percentile(&self, percent: f64) -> Result<Vec<f64>> {
+        if !(percent >= 0.0 && percent <= 100.0) {
+            raise!("the percentile should be between 0 and 100");
+        }
+        if self.step_count == 0 {
+            return Ok(vec![::std::f64::NAN; self.element_count]);
+        }
+        let position = percent / 100.0 * (self.step_count - 1) as f64;
+        let (lower, weight) = (position.floor() as usize, position - position.floor());
+        let upper = ::std::cmp::min(lower + 1, self.step_count - 1);
+        Ok((0..self.element_count).map(|id| {
+            let mut values = self.element(id);
+            values.sort_by(|one, other| {
+                one.partial_cmp(other).unwrap_or_else(|| one.is_nan().cmp(&other.is_nan()))
+            });
+            (1.0 - weight) * values[lower] + weight * values[upper]
+        }).collect())
+    }
+
+    fn check(&self, other: &Profile) -> Result<()> {
+        if self.element_count != other.element_count || self.step_count != other.step_count {
+            raise!("the profiles should have the same shape");
+        }
+        if (self.time_step - other.time_step).abs() > 1e-9 * self.time_step ||
+           (self.time - other.time).abs() > 1e-9 * self.time_step {
+            raise!("the profiles should have the same time base");
+        }
+        Ok(())
+    }
+
+    fn sum(&self) -> Vec<f64> {
+        let mut sum = vec![0.0; self.element_count];
+        for i in 0..self.step_count {
+            for (value, &other) in sum.iter_mut().zip(self.step(i)) {
+                *value += other;
+            }
+        }
+        sum
     }
 }
 
@@ -62,28 +221,54 @@ impl ProfileBuilder {
     #[inline]
     pub fn new(element_count: usize, time_step: f64, fill: Vec<f64>) -> ProfileBuilder {
         debug_assert_eq!(element_count, fill.len());
-        ProfileBuilder { profile: Profile::new(element_count, time_step), fill: fill }
+        ProfileBuilder {
+            element_count: element_count,
+            step_count: 0,
+            time: 0.0,
+            time_step: time_step,
+            data: VecDeque::new(),
+            fill: fill,
+        }
+    }
+
+    /// Return the beginning of the time interval that has not been pulled yet.
+    #[inline(always)]
+    pub fn time(&self) -> f64 {
+        self.time
+    }
+
+    /// Return the time step (sampling interval).
+    #[inline(always)]
+    pub fn time_step(&self) -> f64 {
+        self.time_step
     }
 
     /// Add data to a particular processing element starting from a particular
     /// time moment.
+    ///
+    /// The part of the data preceding the time interval that has not been
+    /// pulled yet is discarded.
     pub fn push(&mut self, element_id: usize, time: f64, time_step: f64, data: &[f64]) {
-        let &mut ProfileBuilder { ref mut profile, ref fill } = self;
-        debug_assert!(element_id < profile.element_count);
-        debug_assert!(time >= profile.time);
-        let (t1, t2) = (profile.time, time);
-        let (d1, d2) = (profile.time_step, time_step);
+        debug_assert!(element_id < self.element_count);
+        let (t1, t2) = (self.time, time);
+        let (d1, d2) = (self.time_step, time_step);
         let s2 = data.len();
+        if t2 + (s2 as f64) * d2 <= t1 {
+            return;
+        }
         let s1 = ((t2 - t1 + (s2 as f64) * d2) / d1).ceil() as usize;
-        if s1 > profile.step_count {
-            let more = s1 - profile.step_count;
-            profile.extend(more, fill);
+        if s1 > self.step_count {
+            let more = s1 - self.step_count;
+            self.extend(more);
         }
-        let mut j1 = ((t2 - t1) / d1) as usize;
-        let mut j2 = 0;
+        let (mut j1, mut j2) = if t2 < t1 {
+            (0, ((t1 - t2) / d2) as usize)
+        } else {
+            (((t2 - t1) / d1) as usize, 0)
+        };
         macro_rules! add(
             ($weight:expr) => (
-                profile.data[j1 * profile.element_count + element_id] += $weight * data[j2];
+                self.data[j1 * self.element_count + element_id] += $weight * data[j2];
             );
         );
         while j1 < s1 && j2 < s2 {
@@ -113,24 +298,42 @@ impl ProfileBuilder {
 
     /// Advance time and return the data accumulated since the previous call.
     pub fn pull(&mut self, time: f64) -> Profile {
-        let &mut ProfileBuilder { ref mut profile, ref fill } = self;
-        debug_assert!(time >= profile.time);
-        let step_count = ((time - profile.time) / profile.time_step).floor() as usize;
-        if profile.step_count < step_count {
-            let more = step_count - profile.step_count;
-            profile.extend(more, fill);
+        debug_assert!(time >= self.time);
+        let step_count = ((time - self.time) / self.time_step).floor() as usize;
+        let time = (time / self.time_step).floor() * self.time_step;
+        self.split(step_count, time)
+    }
+
+    /// Advance time by one time step and return the corresponding data.
+    pub fn step(&mut self) -> Profile {
+        let time = self.time + self.time_step;
+        self.split(1, time)
+    }
+
+    fn extend(&mut self, step_count: usize) {
+        let &mut ProfileBuilder { ref mut data, ref fill, .. } = self;
+        data.reserve(step_count * fill.len());
+        for _ in 0..step_count {
+            data.extend(fill);
+        }
+        self.step_count += step_count;
+    }
+
+    fn split(&mut self, step_count: usize, time: f64) -> Profile {
+        if self.step_count < step_count {
+            let more = step_count - self.step_count;
+            self.extend(more);
         }
-        let mut another = Profile {
-            element_count: profile.element_count,
-            step_count: profile.step_count - step_count,
-            time: (time / profile.time_step).floor() * profile.time_step,
-            time_step: profile.time_step,
-            data: profile.data[(step_count * profile.element_count)..].to_vec(),
+        let profile = Profile {
+            element_count: self.element_count,
+            step_count: step_count,
+            time: self.time,
+            time_step: self.time_step,
+            data: self.data.drain(..(step_count * self.element_count)).collect(),
         };
-        mem::swap(profile, &mut another);
-        another.step_count = step_count;
-        another.data.truncate(step_count * profile.element_count);
-        another
+        self.step_count -= step_count;
+        self.time = time;
+        profile
     }
 }
 
@@ -144,16 +347,40 @@ impl Into<Vec<f64>> for Profile {
 deref! { Profile::data => [f64] }
 deref! { mut Profile::data => [f64] }
 
+// Combine two profiles of the same shape and time base element by element.
+macro_rules! arithmetic(
+    ($method:ident, $operator:tt) => (
+        impl Profile {
+            /// Combine with another profile of the same shape and time base
+            /// element by element.
+            pub fn $method(&self, other: &Profile) -> Result<Profile> {
+                try!(self.check(other));
+                let mut result = self.clone();
+                for (value, &other) in result.data.iter_mut().zip(&other.data) {
+                    *value = *value $operator other;
+                }
+                Ok(result)
+            }
+        }
+    );
+);
+
+arithmetic!(checked_add, +);
+arithmetic!(checked_div, /);
+arithmetic!(checked_mul, *);
+arithmetic!(checked_sub, -);
+
 #[cfg(test)]
 mod tests {
-    use super::ProfileBuilder;
+    use assert;
+    use super::{Profile, ProfileBuilder, Reduction};
 
     macro_rules! eq(
         (&$builder:ident.$field:ident, $value:expr) => (
-            assert_eq!(&$builder.profile.$field, $value);
+            assert_eq!(&$builder.$field, $value);
         );
         ($builder:ident.$field:ident, $value:expr) => (
-            assert_eq!($builder.profile.$field, $value);
+            assert_eq!($builder.$field, $value);
         );
         ($left:expr, $right:expr) => (
             assert_eq!($left, $right);
@@ -246,6 +473,28 @@ mod tests {
         ]);
     }
 
+    #[test]
+    fn push_past() {
+        let mut builder = ProfileBuilder::new(2, 1.0, vec![0.0, 0.0]);
+        builder.pull(2.0);
+
+        builder.push(0, 0.5, 1.0, &[1.0, 2.0, 3.0, 4.0]);
+        eq!(builder.time, 2.0);
+        eq!(builder.step_count, 3);
+        eq!(&builder.data, &vec![
+           2.5, 0.0,
+           3.5, 0.0,
+           2.0, 0.0,
+        ]);
+
+        builder.push(1, 0.0, 1.0, &[1.0, 2.0]);
+        eq!(builder.step_count, 3);
+
+        eq!(builder.step().data, vec![2.5, 0.0]);
+        eq!(builder.time, 3.0);
+        eq!(builder.step_count, 2);
+    }
+
     #[test]
     fn pull() {
         let mut builder = ProfileBuilder::new(2, 1.0, vec![0.0, 0.0]);
@@ -273,4 +522,64 @@ mod tests {
         eq!(builder.time, 3.0);
         eq!(builder.step_count, 39);
     }
+
+    #[test]
+    fn downsample() {
+        let profile = Profile {
+            element_count: 2,
+            step_count: 5,
+            time: 1.0,
+            time_step: 0.5,
+            data: vec![1.0, 2.0, 3.0, 0.0, 5.0, 6.0, 1.0, 0.0, 2.0, 4.0],
+        };
+        let other = profile.downsample(1.0, Reduction::Mean).unwrap();
+        eq!(other.step_count, 3);
+        eq!(other.time_step, 1.0);
+        eq!(&other.data, &vec![2.0, 1.0, 3.0, 3.0, 2.0, 4.0]);
+        let other = profile.downsample(1.0, Reduction::Max).unwrap();
+        eq!(&other.data, &vec![3.0, 2.0, 5.0, 6.0, 2.0, 4.0]);
+        assert!(profile.downsample(0.75, Reduction::Mean).is_err());
+    }
+
+    #[test]
+    fn statistics() {
+        let mut profile = Profile {
+            element_count: 2,
+            step_count: 2,
+            time: 0.0,
+            time_step: 0.5,
+            data: vec![1.0, 8.0, 4.0, 2.0],
+        };
+        profile.append(&Profile {
+            element_count: 2,
+            step_count: 2,
+            time: 1.0,
+            time_step: 0.5,
+            data: vec![3.0, 6.0, 2.0, 4.0],
+        }).unwrap();
+        eq!(profile.step_count, 4);
+        eq!(profile.element(1), vec![8.0, 2.0, 6.0, 4.0]);
+        assert::close(&profile.energy(), &[5.0, 10.0], 1e-14);
+        assert::close(&profile.mean(), &[2.5, 5.0], 1e-14);
+        assert::close(&profile.peak(), &[4.0, 8.0], 1e-14);
+        assert::close(&profile.percentile(50.0).unwrap(), &[2.5, 5.0], 1e-14);
+        assert::close(&profile.percentile(75.0).unwrap(), &[3.25, 6.5], 1e-14);
+        assert!(profile.percentile(100.5).is_err());
+        assert!(profile.percentile(-1.0).is_err());
+        assert!(profile.percentile(::std::f64::NAN).is_err());
+        let other = profile.checked_sub(&profile.clone_zero()).unwrap();
+        eq!(&other.data, &profile.data);
+        let other = profile.checked_mul(&profile).unwrap();
+        eq!(other.data[1], 64.0);
+
+        let mut other = profile.clone();
+        other.time += other.time_step;
+        assert!(profile.checked_add(&other).is_err());
+        assert!(profile.checked_sub(&profile.downsample(1.0, Reduction::Mean).unwrap()).is_err());
+        eq!(profile.checked_div(&profile).unwrap().data, vec![1.0; 8]);
+
+        profile.data[2] = ::std::f64::NAN;
+        eq!(profile.percentile(0.0).unwrap()[0], 1.0);
+        assert!(profile.percentile(100.0).unwrap()[0].is_nan());
+    }
 }
@@ -0,0 +1,256 @@
+use {Config, Result};
+use platform::Profile;
+
+const BOLTZMANN: f64 = 8.617330350e-5;
+const YEAR: f64 = 365.25 * 24.0 * 60.0 * 60.0;
+
+/// A reliability model accumulating the wear of processing elements.
+///
+/// Electromigration and time-dependent dielectric breakdown are modeled by
+/// Arrhenius-type mean times to failure, and the damage they cause is the
+/// time spent at each temperature divided by the corresponding mean time to
+/// failure. Thermal cycling is modeled by the Coffin–Manson relation applied to
+/// the cycles extracted from the temperature of each processing element by
+/// rainflow counting. The damage of the three mechanisms is summed up, and a
+/// processing element is considered worn out when its damage reaches one.
+pub struct Reliability {
+    electromigration: Arrhenius,
+    tddb: Arrhenius,
+    cycling: CoffinManson,
+    /// Whether the accumulated damage should be reported at each time step.
+    pub profile: bool,
+    time: f64,
+    wears: Vec<Wear>,
+    counters: Vec<Rainflow>,
+}
+
+/// The wear of a processing element.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Wear {
+    /// The damage due to electromigration.
+    pub electromigration: f64,
+    /// The damage due to time-dependent dielectric breakdown.
+    pub tddb: f64,
+    /// The damage due to thermal cycling.
+    pub cycling: f64,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Arrhenius {
+    mttf: f64,
+    activation_energy: f64,
+    reference_temperature: f64,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct CoffinManson {
+    cycles: f64,
+    reference_range: f64,
+    exponent: f64,
+    threshold: f64,
+}
+
+// The reversals of a temperature history that have not formed a cycle yet.
+// The last one is tentative as the history might continue in its direction.
+struct Rainflow {
+    reversals: Vec<f64>,
+}
+
+impl Reliability {
+    /// Create a model.
+    ///
+    /// The parameters of the mechanisms are given in sections named
+    /// `electromigration`, `tddb`, and `cycling`. The mean times to failure
+    /// are given in years.
+    pub fn new(element_count: usize, config: &Config) -> Result<Reliability> {
+        macro_rules! branch(($name:expr) => (config.branch($name).as_ref().unwrap_or(config)));
+        Ok(Reliability {
+            electromigration: try!(Arrhenius::new(branch!("electromigration"), 0.9)),
+            tddb: try!(Arrhenius::new(branch!("tddb"), 0.75)),
+            cycling: try!(CoffinManson::new(branch!("cycling"))),
+            profile: *config.get::<bool>("profile").unwrap_or(&false),
+            time: 0.0,
+            wears: vec![Wear::default(); element_count],
+            counters: (0..element_count).map(|_| Rainflow::new()).collect(),
+        })
+    }
+
+    /// Take into account a temperature profile and return the total damage
+    /// accumulated by the end of each time step.
+    pub fn next(&mut self, temperature: &Profile) -> Profile {
+        let mut damage = temperature.clone_zero();
+        let element_count = temperature.element_count;
+        for i in 0..temperature.step_count {
+            for j in 0..element_count {
+                let k = i * element_count + j;
+                let (wear, value) = (&mut self.wears[j], temperature[k]);
+                wear.electromigration += self.electromigration.damage(value,
+                                                                      temperature.time_step);
+                wear.tddb += self.tddb.damage(value, temperature.time_step);
+                let cycling = &self.cycling;
+                wear.cycling += self.counters[j].push(value, |range| cycling.damage(range));
+                damage[k] = wear.total();
+            }
+        }
+        self.time += temperature.step_count as f64 * temperature.time_step;
+        damage
+    }
+
+    /// Return the wear of the processing elements.
+    ///
+    /// The temperature variations that have not formed complete cycles are
+    /// counted as half cycles.
+    pub fn wears(&self) -> Vec<Wear> {
+        self.wears.iter().zip(&self.counters).map(|(wear, counter)| {
+            let mut wear = *wear;
+            wear.cycling += counter.residue(|range| self.cycling.damage(range));
+            wear
+        }).collect()
+    }
+
+    /// Return the mean time to failure of the processing elements estimated
+    /// by extrapolating the damage accumulated so far.
+    ///
+    /// The estimate is missing for the processing elements that have not
+    /// accumulated any damage.
+    pub fn mttf(&self) -> Vec<Option<f64>> {
+        self.wears().iter().map(|wear| {
+            let total = wear.total();
+            if total > 0.0 { Some(self.time / total) } else { None }
+        }).collect()
+    }
+}
+
+impl Wear {
+    /// Return the total damage.
+    #[inline]
+    pub fn total(&self) -> f64 {
+        self.electromigration + self.tddb + self.cycling
+    }
+}
+
+impl Default for Wear {
+    #[inline]
+    fn default() -> Wear {
+        Wear { electromigration: 0.0, tddb: 0.0, cycling: 0.0 }
+    }
+}
+
+impl Arrhenius {
+    fn new(config: &Config, activation_energy: f64) -> Result<Arrhenius> {
+        let model = Arrhenius {
+            mttf: YEAR * *config.get::<f64>("mttf").unwrap_or(&30.0),
+            activation_energy: *config.get::<f64>("activation_energy")
+                                      .unwrap_or(&activation_energy),
+            reference_temperature: *config.get::<f64>("reference_temperature")
+                                          .unwrap_or(&345.15),
+        };
+        if model.mttf <= 0.0 || model.activation_energy < 0.0 ||
+           model.reference_temperature <= 0.0 {
+            raise!("found invalid parameters of a reliability model");
+        }
+        Ok(model)
+    }
+
+    #[inline]
+    fn damage(&self, temperature: f64, time: f64) -> f64 {
+        let exponent = self.activation_energy / BOLTZMANN *
+                       (1.0 / self.reference_temperature - 1.0 / temperature);
+        time * exponent.exp() / self.mttf
+    }
+}
+
+impl CoffinManson {
+    fn new(config: &Config) -> Result<CoffinManson> {
+        let model = CoffinManson {
+            cycles: *config.get::<f64>("cycles").unwrap_or(&1e6),
+            reference_range: *config.get::<f64>("reference_range").unwrap_or(&10.0),
+            exponent: *config.get::<f64>("exponent").unwrap_or(&2.35),
+            threshold: *config.get::<f64>("threshold").unwrap_or(&0.0),
+        };
+        if model.cycles <= 0.0 || model.reference_range <= 0.0 || model.exponent <= 0.0 ||
+           model.threshold < 0.0 {
+            raise!("found invalid parameters of the thermal-cycling model");
+        }
+        Ok(model)
+    }
+
+    // Return the damage caused by one cycle of a particular range.
+    #[inline]
+    fn damage(&self, range: f64) -> f64 {
+        if range <= self.threshold {
+            return 0.0;
+        }
+        ((range - self.threshold) / self.reference_range).powf(self.exponent) / self.cycles
+    }
+}
+
+impl Rainflow {
+    #[inline]
+    fn new() -> Rainflow {
+        Rainflow { reversals: vec![] }
+    }
+
+    // Take into account a value and return the damage of the cycles completed.
+    fn push<F>(&mut self, value: f64, mut damage: F) -> f64 where F: FnMut(f64) -> f64 {
+        let reversals = &mut self.reversals;
+        let count = reversals.len();
+        if count == 1 && reversals[0] == value {
+            return 0.0;
+        }
+        if count > 1 &&
+           (reversals[count - 1] - reversals[count - 2]) * (value - reversals[count - 1]) >= 0.0 {
+            reversals[count - 1] = value;
+            return 0.0;
+        }
+        reversals.push(value);
+        let mut total = 0.0;
+        loop {
+            let count = reversals.len() - 1;
+            if count < 3 {
+                break;
+            }
+            let x = (reversals[count - 1] - reversals[count - 2]).abs();
+            let y = (reversals[count - 2] - reversals[count - 3]).abs();
+            if x < y {
+                break;
+            }
+            if count == 3 {
+                total += 0.5 * damage(y);
+                reversals.remove(0);
+            } else {
+                total += damage(y);
+                reversals.drain((count - 3)..(count - 1));
+            }
+        }
+        total
+    }
+
+    // Return the damage of the half cycles formed by the remaining reversals.
+    fn residue<F>(&self, mut damage: F) -> f64 where F: FnMut(f64) -> f64 {
+        self.reversals.windows(2).fold(0.0, |sum, pair| {
+            sum + 0.5 * damage((pair[1] - pair[0]).abs())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Rainflow;
+
+    #[test]
+    fn rainflow() {
+        let mut counter = Rainflow::new();
+        let (mut ranges, mut total) = (vec![], 0.0);
+        for &value in &[-2.0, 1.0, -3.0, 5.0, -1.0, 3.0, -4.0, 4.0, -2.0] {
+            total += counter.push(value, |range| {
+                ranges.push(range);
+                range
+            });
+        }
+        assert_eq!(ranges, vec![3.0, 4.0, 4.0, 8.0]);
+        assert_eq!(total, 11.5);
+        assert_eq!(counter.residue(|range| range), 11.5);
+        assert_eq!(counter.reversals, vec![5.0, -4.0, 4.0, -2.0]);
+    }
+}
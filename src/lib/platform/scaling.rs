@@ -0,0 +1,131 @@
+use {Config, Result};
+use platform::Element;
+use workload::Component;
+
+/// A rule for scaling the dynamic power of workload patterns to a platform.
+///
+/// Patterns are typically profiled on a chip that differs from the simulated
+/// one. If `area` is set, the dynamic power of a component is scaled by the
+/// ratio of the area of the hosting processing element to the area of the
+/// component, which preserves the power density. The dynamic power is also
+/// scaled according to the technologies of the two chips described in the
+/// `source` and `target` sections: the switched capacitance is assumed to be
+/// proportional to the feature size (`node`), and the dynamic power is
+/// proportional to the capacitance, the supply voltage (`voltage`) squared,
+/// and the frequency (`frequency`). Each quantity should be either given for
+/// both technologies or omitted for both. Any other adjustment can be given
+/// as a plain `factor`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Scaling {
+    /// Whether the dynamic power should be scaled by the area ratio.
+    pub area: bool,
+    /// The factor due to the differences in technology.
+    pub factor: f64,
+}
+
+impl Scaling {
+    /// Create a rule.
+    pub fn new(config: &Config) -> Result<Scaling> {
+        let mut factor = *config.get::<f64>("factor").unwrap_or(&1.0);
+        if factor < 0.0 {
+            raise!("the factor of dynamic-power scaling should be nonnegative");
+        }
+        let (source, target) = (config.branch("source"), config.branch("target"));
+        for &(name, exponent) in &[("node", 1), ("voltage", 2), ("frequency", 1)] {
+            let source = source.as_ref().and_then(|config| config.get::<f64>(name));
+            let target = target.as_ref().and_then(|config| config.get::<f64>(name));
+            match (source, target) {
+                (Some(&source), Some(&target)) if source > 0.0 && target > 0.0 => {
+                    factor *= (target / source).powi(exponent);
+                },
+                (None, None) => {},
+                _ => raise!("the {} should be given and positive for both the source and \
+                             target technologies", name),
+            }
+        }
+        Ok(Scaling {
+            area: *config.get::<bool>("area").unwrap_or(&false),
+            factor: factor,
+        })
+    }
+
+    /// Return the factor by which the dynamic power of a workload component
+    /// is scaled when it runs on a processing element.
+    #[inline]
+    pub fn factor(&self, component: &Component, element: &Element) -> f64 {
+        if self.area {
+            debug_assert!(element.area > 0.0 && component.area > 0.0);
+            self.factor * element.area / component.area
+        } else {
+            self.factor
+        }
+    }
+}
+
+impl Default for Scaling {
+    #[inline]
+    fn default() -> Scaling {
+        Scaling { area: false, factor: 1.0 }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use configuration::format::TOML;
+    use std::collections::HashMap;
+
+    use platform::{Element, ElementCapacity, ElementKind};
+    use workload::Component;
+    use super::Scaling;
+
+    #[test]
+    fn factor() {
+        let kind = ElementKind::new("core");
+        let element = Element::new(0, "Core0".to_string(), kind.clone(), ElementCapacity::Single,
+                                   2.0, 0);
+        let component = Component {
+            name: "core0".to_string(),
+            kind: kind.clone(),
+            family: kind,
+            performance: 1.0,
+            power: 1.0,
+            area: 4.0,
+            leakage_power: 0.0,
+            dynamic_power: vec![],
+            metrics: HashMap::new(),
+        };
+        assert_eq!(Scaling::default().factor(&component, &element), 1.0);
+        let scaling = Scaling { area: true, factor: 0.5 };
+        assert_eq!(scaling.factor(&component, &element), 0.25);
+    }
+
+    #[test]
+    fn new() {
+        let config = TOML::parse(r#"
+            factor = 2.0
+
+            [source]
+            node = 45.0
+            voltage = 1.2
+            frequency = 2e9
+
+            [target]
+            node = 22.5
+            voltage = 0.6
+            frequency = 3e9
+        "#).unwrap();
+        let scaling = Scaling::new(&config).unwrap();
+        assert!(!scaling.area);
+        assert_eq!(scaling.factor, 2.0 * 0.5 * 0.25 * 1.5);
+
+        let config = TOML::parse(r#"
+            [source]
+            node = 45.0
+            voltage = 1.2
+
+            [target]
+            node = 22.5
+        "#).unwrap();
+        assert!(Scaling::new(&config).is_err());
+    }
+}
@@ -0,0 +1,159 @@
+use probability::distribution::{Gaussian, Sample};
+use std::collections::VecDeque;
+
+use {Config, Result, Source};
+use platform::{Element, Profile};
+
+/// A model of temperature sensors, one per processing element.
+///
+/// A sensor samples the temperature with a certain period, adds a bias and
+/// Gaussian noise, quantizes the result, and makes it available after a
+/// certain latency. Between readings, the last available reading is held.
+pub struct Sensors {
+    sensors: Vec<Sensor>,
+    states: Vec<State>,
+    gaussian: Gaussian,
+    source: Source,
+}
+
+/// A temperature sensor.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Sensor {
+    /// The standard deviation of the noise.
+    pub noise: f64,
+    /// The bias.
+    pub bias: f64,
+    /// The resolution (zero for no quantization).
+    pub resolution: f64,
+    /// The sampling period (zero for sampling at every time step).
+    pub period: f64,
+    /// The delay before a reading becomes available.
+    pub latency: f64,
+}
+
+struct State {
+    next: f64,
+    value: f64,
+    pending: VecDeque<(f64, f64)>,
+}
+
+impl Sensors {
+    /// Create sensors given the initial temperature.
+    ///
+    /// The settings can be overridden for a type of processing elements in a
+    /// section named after the type and for a processing element in a section
+    /// named after the element.
+    pub fn new(elements: &[Element], temperature: f64, config: &Config, source: Source)
+               -> Result<Sensors> {
+        let mut sensors = Vec::with_capacity(elements.len());
+        let mut states = Vec::with_capacity(elements.len());
+        for element in elements {
+            let branch = config.branch(&element.name)
+                               .or_else(|| config.branch(&element.kind.to_string()));
+            let sensor = try!(Sensor::new(branch.as_ref().unwrap_or(config)));
+            states.push(State {
+                next: 0.0,
+                value: sensor.quantize(temperature + sensor.bias),
+                pending: VecDeque::new(),
+            });
+            sensors.push(sensor);
+        }
+        Ok(Sensors {
+            sensors: sensors,
+            states: states,
+            gaussian: Gaussian::new(0.0, 1.0),
+            source: source,
+        })
+    }
+
+    /// Take into account a temperature profile and return the corresponding
+    /// sensed temperature profile.
+    pub fn next(&mut self, temperature: &Profile) -> Profile {
+        let mut sensed = temperature.clone_zero();
+        let element_count = temperature.element_count;
+        for i in 0..temperature.step_count {
+            let time = temperature.time + ((i + 1) as f64) * temperature.time_step;
+            for j in 0..element_count {
+                let k = i * element_count + j;
+                let (sensor, state) = (&self.sensors[j], &mut self.states[j]);
+                if time >= state.next {
+                    let noise = if sensor.noise > 0.0 {
+                        sensor.noise * self.gaussian.sample(&mut self.source)
+                    } else {
+                        0.0
+                    };
+                    let value = sensor.quantize(temperature[k] + sensor.bias + noise);
+                    state.pending.push_back((time + sensor.latency, value));
+                    state.next = if sensor.period > 0.0 {
+                        ((time / sensor.period).floor() + 1.0) * sensor.period
+                    } else {
+                        time
+                    };
+                }
+                while state.pending.front().map(|&(ready, _)| ready <= time).unwrap_or(false) {
+                    state.value = state.pending.pop_front().unwrap().1;
+                }
+                sensed[k] = state.value;
+            }
+        }
+        sensed
+    }
+}
+
+impl Sensor {
+    /// Create a sensor.
+    pub fn new(config: &Config) -> Result<Sensor> {
+        macro_rules! get(
+            ($name:expr) => (*config.get::<f64>($name).unwrap_or(&0.0));
+        );
+        let sensor = Sensor {
+            noise: get!("noise"),
+            bias: get!("bias"),
+            resolution: get!("resolution"),
+            period: get!("period"),
+            latency: get!("latency"),
+        };
+        if sensor.noise < 0.0 || sensor.resolution < 0.0 {
+            raise!("the noise and resolution of a sensor should be nonnegative");
+        }
+        if sensor.period < 0.0 || sensor.latency < 0.0 {
+            raise!("the period and latency of a sensor should be nonnegative");
+        }
+        Ok(sensor)
+    }
+
+    /// Quantize a temperature.
+    pub fn quantize(&self, temperature: f64) -> f64 {
+        if self.resolution > 0.0 {
+            (temperature / self.resolution).round() * self.resolution
+        } else {
+            temperature
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use probability::distribution::Gaussian;
+    use random;
+    use std::collections::VecDeque;
+
+    use platform::Profile;
+    use super::{Sensor, Sensors, State};
+
+    #[test]
+    fn next() {
+        let sensor = Sensor { noise: 0.0, bias: 1.0, resolution: 0.5, period: 2.0, latency: 1.0 };
+        let mut sensors = Sensors {
+            sensors: vec![sensor],
+            states: vec![State { next: 0.0, value: 300.0, pending: VecDeque::new() }],
+            gaussian: Gaussian::new(0.0, 1.0),
+            source: random::default(),
+        };
+        let mut temperature = Profile::new(1, 1.0);
+        temperature.step_count = 6;
+        temperature.data = vec![310.1, 320.2, 330.3, 340.4, 350.5, 360.6];
+        let sensed = sensors.next(&temperature);
+        assert_eq!(&sensed.data, &vec![300.0, 311.0, 321.0, 321.0, 341.5, 341.5]);
+    }
+}
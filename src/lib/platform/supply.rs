@@ -0,0 +1,187 @@
+use {Config, Result};
+use platform::{Element, Profile};
+
+/// A model of power delivery, one network per processing element.
+///
+/// The supply current of a processing element is its power divided by its
+/// supply voltage. The current is drawn from an on-chip decoupling capacitance
+/// that is connected to an ideal voltage regulator through a series resistance
+/// and inductance, which is a lumped RLC model of the power-delivery network.
+/// The droop is the difference between the nominal voltage and the voltage
+/// across the capacitance; the largest droop observed within a time step is
+/// reported for that step. The current is held constant during a time step,
+/// and each time step is divided into a number of substeps in order to capture
+/// the oscillations caused by changes in the current.
+pub struct Supply {
+    networks: Vec<Network>,
+    states: Vec<State>,
+    substeps: usize,
+}
+
+/// A power-delivery network.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Network {
+    /// The nominal supply voltage.
+    pub voltage: f64,
+    /// The series resistance.
+    pub resistance: f64,
+    /// The series inductance.
+    pub inductance: f64,
+    /// The decoupling capacitance.
+    pub capacitance: f64,
+}
+
+// The state of a network is the current through the inductance and the
+// deviation of the voltage across the capacitance from the nominal one. It is
+// propagated over a substep as x = transition * x + input * current.
+#[derive(Clone, Copy, Debug)]
+struct State {
+    value: [f64; 2],
+    transition: [f64; 4],
+    input: [f64; 2],
+}
+
+impl Supply {
+    /// Create a model.
+    ///
+    /// The settings can be overridden for a type of processing elements in a
+    /// section named after the type and for a processing element in a section
+    /// named after the element.
+    pub fn new(elements: &[Element], time_step: f64, config: &Config) -> Result<Supply> {
+        let substeps = *config.get::<i64>("substeps").unwrap_or(&1);
+        if substeps <= 0 {
+            raise!("the number of substeps of power delivery should be positive");
+        }
+        let mut networks = Vec::with_capacity(elements.len());
+        let mut states = Vec::with_capacity(elements.len());
+        for element in elements {
+            let branch = config.branch(&element.name)
+                               .or_else(|| config.branch(&element.kind.to_string()));
+            let network = try!(Network::new(branch.as_ref().unwrap_or(config)));
+            states.push(State::new(&network, time_step / substeps as f64));
+            networks.push(network);
+        }
+        Ok(Supply { networks: networks, states: states, substeps: substeps as usize })
+    }
+
+    /// Take into account a power profile and return the corresponding supply
+    /// current and voltage droop profiles.
+    pub fn next(&mut self, power: &Profile) -> (Profile, Profile) {
+        let mut current = power.clone_zero();
+        let mut droop = power.clone_zero();
+        let element_count = power.element_count;
+        for i in 0..power.step_count {
+            for j in 0..element_count {
+                let k = i * element_count + j;
+                let (network, state) = (&self.networks[j], &mut self.states[j]);
+                current[k] = power[k] / network.voltage;
+                let mut peak = ::std::f64::NEG_INFINITY;
+                for _ in 0..self.substeps {
+                    state.advance(current[k]);
+                    peak = peak.max(-state.value[1]);
+                }
+                droop[k] = peak;
+            }
+        }
+        (current, droop)
+    }
+}
+
+impl Network {
+    /// Create a network.
+    ///
+    /// The quantities are in volts, ohms, henries, and farads.
+    pub fn new(config: &Config) -> Result<Network> {
+        let network = Network {
+            voltage: *some!(config.get::<f64>("voltage"), "a supply voltage is required"),
+            resistance: *config.get::<f64>("resistance").unwrap_or(&1e-3),
+            inductance: *config.get::<f64>("inductance").unwrap_or(&1e-9),
+            capacitance: *config.get::<f64>("capacitance").unwrap_or(&1e-6),
+        };
+        if network.voltage <= 0.0 || network.resistance < 0.0 {
+            raise!("the voltage and resistance of power delivery should be valid");
+        }
+        if network.inductance <= 0.0 || network.capacitance <= 0.0 {
+            raise!("the inductance and capacitance of power delivery should be positive");
+        }
+        Ok(network)
+    }
+}
+
+impl State {
+    // Discretize the dynamics
+    //
+    //     L di/dt = -R i - v,
+    //     C dv/dt = i - current,
+    //
+    // exactly, assuming the current is constant during the time step.
+    fn new(network: &Network, time_step: f64) -> State {
+        let &Network { resistance, inductance, capacitance, .. } = network;
+        let system = [-resistance / inductance, -1.0 / inductance, 1.0 / capacitance, 0.0];
+        let transition = exponential(&system, time_step);
+        // input = system^(-1) (transition - identity) [0, -1 / C]
+        let determinant = 1.0 / (inductance * capacitance);
+        let (one, other) = (transition[1] / capacitance,
+                            (transition[3] - 1.0) / capacitance);
+        State {
+            value: [0.0, 0.0],
+            transition: transition,
+            input: [-(system[3] * one - system[1] * other) / determinant,
+                    -(system[0] * other - system[2] * one) / determinant],
+        }
+    }
+
+    #[inline]
+    fn advance(&mut self, current: f64) {
+        let &mut State { ref mut value, ref transition, ref input } = self;
+        *value = [transition[0] * value[0] + transition[1] * value[1] + input[0] * current,
+                  transition[2] * value[0] + transition[3] * value[1] + input[1] * current];
+    }
+}
+
+// Compute the exponential of a 2-by-2 matrix multiplied by a scalar using the
+// eigenvalues mu ± nu of the matrix.
+fn exponential(matrix: &[f64; 4], time: f64) -> [f64; 4] {
+    let mu = (matrix[0] + matrix[3]) / 2.0;
+    let determinant = matrix[0] * matrix[3] - matrix[1] * matrix[2];
+    let discriminant = mu * mu - determinant;
+    let nu = discriminant.abs().sqrt();
+    let (even, odd) = if nu * time < 1e-8 {
+        (1.0, time)
+    } else if discriminant < 0.0 {
+        ((nu * time).cos(), (nu * time).sin() / nu)
+    } else {
+        ((nu * time).cosh(), (nu * time).sinh() / nu)
+    };
+    let scale = (mu * time).exp();
+    [scale * (even + odd * (matrix[0] - mu)), scale * odd * matrix[1],
+     scale * odd * matrix[2], scale * (even + odd * (matrix[3] - mu))]
+}
+
+#[cfg(test)]
+mod tests {
+    use platform::Profile;
+    use super::{Network, State, Supply};
+
+    #[test]
+    fn next() {
+        let network = Network {
+            voltage: 1.0,
+            resistance: 1e-3,
+            inductance: 1e-9,
+            capacitance: 1e-6,
+        };
+        let mut supply = Supply {
+            networks: vec![network],
+            states: vec![State::new(&network, 1e-8)],
+            substeps: 100,
+        };
+        let mut power = Profile::new(1, 1e-6);
+        power.step_count = 100;
+        power.data = vec![10.0; 100];
+        let (current, droop) = supply.next(&power);
+        assert!(current.data.iter().all(|&current| current == 10.0));
+        assert!(droop[0] > 1.5e-2);
+        assert!((droop[99] - 1e-2).abs() < 1e-4);
+    }
+}
@@ -1,100 +1,513 @@
+use std::mem;
 use std::path::Path;
-use std::str::FromStr;
 use temperature::{self, Simulator};
 
-use {Config, Result};
-use platform::{Element, ElementKind, Platform, Profile, ProfileBuilder};
+use {Config, Result, Source};
+use platform::{Compact, Conditions, Data, Dtm, DtmPolicy, Element, ElementClassifier};
+use platform::{Floorplan, Generator, Grid};
+use platform::{Map, Notice, Platform, Power, Probe, Profile, Reliability, Sensors};
+use platform::Variation;
 use schedule::Mapping;
 use system::Job;
-use workload::Component;
 
 /// A platform producing power and temperature data.
+///
+/// When the compact thermal model is discretized into a grid, the temperature
+/// map of the die and the peak temperature of each processing element are
+/// reported as well as the temperature at the points of the die given by
+/// probes; these outputs are not available for HotSpot and 3D-ICE. When
+/// the compact thermal model is used, the ambient temperature and the heat
+/// transfer coefficient of the heat sink can follow a time series.
 pub struct Thermal {
-    elements: Vec<Element>,
+    power: Power,
     simulator: Simulator,
-    builder: ProfileBuilder,
+    temperature: Vec<f64>,
+    grid: Option<Grid>,
+    probes: Vec<Probe>,
+    cells: Vec<f64>,
+    cooling: Option<Cooling>,
+    dtm: Option<Dtm>,
+    sensors: Option<Sensors>,
+    reliability: Option<Reliability>,
+    variation: Option<Variation>,
+    notices: Vec<Notice>,
+}
+
+// The deviation of the cooling from the nominal conditions the thermal circuit
+// has been built for. It is accounted for by injecting into the heat sink the
+// difference between the nominal and the actual heat flow to the ambience,
+// which is computed using the temperature of the sink at the previous step.
+struct Cooling {
+    conditions: Conditions,
+    ambience: f64,
+    conductance: f64,
+    area: f64,
+    sink: f64,
+}
+
+impl Cooling {
+    fn inject(&self, time: f64) -> f64 {
+        let (ambience, coefficient) = self.conditions.at(time);
+        self.conductance * (self.sink - self.ambience) -
+            coefficient * self.area * (self.sink - ambience)
+    }
 }
 
 impl Thermal {
     /// Create a platform.
-    pub fn new(config: &Config) -> Result<Thermal> {
-        let (elements, simulator) = try!(construct_temperature(config.branch("temperature")
-                                                                     .as_ref()
-                                                                     .unwrap_or(config)));
-        let builder = try!(construct_power(&elements, config.branch("power")
-                                                            .as_ref()
-                                                            .unwrap_or(config)));
-        Ok(Thermal { elements: elements, simulator: simulator, builder: builder })
+    pub fn new(config: &Config, classifier: &ElementClassifier, mut source: Source)
+               -> Result<Thermal> {
+        macro_rules! branch(($name:expr) => (config.branch($name).as_ref().unwrap_or(config)));
+        let mut variation = match config.branch("variation") {
+            Some(ref config) => Some(try!(Variation::new(config, &mut source))),
+            _ => None,
+        };
+        let generator = match config.branch("generator") {
+            Some(ref config) => Some(try!(Generator::new(config))),
+            _ => None,
+        };
+        let (elements, simulator, ambience, grid, cooling) = try!(construct_temperature(
+            classifier, branch!("temperature"), generator.as_ref(), variation.as_ref()));
+        let element_count = elements.len();
+        if let Some(ref mut variation) = variation {
+            try!(variation.sample(&elements, &mut source));
+        }
+        let mut probes = vec![];
+        if let Some(ref configs) = branch!("temperature").forest("probes") {
+            if grid.is_none() {
+                raise!("probes require the compact thermal model with a grid");
+            }
+            for config in configs {
+                probes.push(try!(Probe::new(config)));
+            }
+        }
+        let sensors = match config.branch("sensors") {
+            Some(ref config) => Some(try!(Sensors::new(&elements, ambience, config, source))),
+            _ => None,
+        };
+        let mut power = try!(Power::with_elements(elements, classifier, config));
+        if let Some(ref variation) = variation {
+            power.vary(&variation.leakage);
+        }
+        let reliability = match config.branch("reliability") {
+            Some(ref config) => Some(try!(Reliability::new(element_count, config))),
+            _ => None,
+        };
+        let dtm = match config.branch("dtm") {
+            Some(ref config) => Some(try!(Dtm::new(element_count, config))),
+            _ => None,
+        };
+        Ok(Thermal {
+            power: power,
+            simulator: simulator,
+            temperature: vec![ambience; element_count],
+            grid: grid,
+            probes: probes,
+            cells: vec![],
+            cooling: cooling,
+            dtm: dtm,
+            sensors: sensors,
+            reliability: reliability,
+            variation: variation,
+            notices: vec![],
+        })
+    }
+
+    fn next_plain(&mut self, time: f64) -> (Profile, Profile, Profile) {
+        let (mut power, state) = self.power.pull(time);
+        let mut temperature = power.clone_zero();
+        let (element_count, step_count) = (power.element_count, power.step_count);
+        if step_count == 0 {
+            return (power, state, temperature);
+        }
+        if self.power.leakage().is_constant() {
+            for i in 0..step_count {
+                let range = (i * element_count)..((i + 1) * element_count);
+                self.power.leak(&self.temperature, &state[range.clone()], &mut power[range]);
+            }
+            self.simulate(power.time, &power, &mut temperature);
+            let range = ((step_count - 1) * element_count)..(step_count * element_count);
+            self.temperature.copy_from_slice(&temperature[range]);
+        } else {
+            for i in 0..step_count {
+                let range = (i * element_count)..((i + 1) * element_count);
+                self.power.leak(&self.temperature, &state[range.clone()],
+                                &mut power[range.clone()]);
+                let time = power.time + i as f64 * power.time_step;
+                self.simulate(time, &power[range.clone()], &mut temperature[range.clone()]);
+                self.temperature.copy_from_slice(&temperature[range]);
+            }
+        }
+        (power, state, temperature)
+    }
+
+    fn next_managed(&mut self, time: f64) -> (Profile, Profile, Profile) {
+        let element_count = self.temperature.len();
+        let (start, time_step) = (self.power.time(), self.power.time_step());
+        let step_count = ((time - start) / time_step).floor().max(0.0) as usize;
+        let mut power = Vec::with_capacity(step_count * element_count);
+        let mut state = Vec::with_capacity(step_count * element_count);
+        let mut temperature = vec![0.0; step_count * element_count];
+        for i in 0..step_count {
+            let time = self.power.time();
+            let (mut step_power, step_state) = self.power.step();
+            self.power.leak(&self.temperature, &step_state, &mut step_power);
+            let range = (i * element_count)..((i + 1) * element_count);
+            self.simulate(time, &step_power, &mut temperature[range.clone()]);
+            self.temperature.copy_from_slice(&temperature[range]);
+            power.extend(&step_power[..]);
+            state.extend(&step_state[..]);
+            self.manage();
+        }
+        macro_rules! profile(
+            ($data:expr) => (Profile {
+                element_count: element_count,
+                step_count: step_count,
+                time: start,
+                time_step: time_step,
+                data: $data,
+            });
+        );
+        (profile!(power), profile!(state), profile!(temperature))
+    }
+
+    fn simulate(&mut self, time: f64, power: &[f64], temperature: &mut [f64]) {
+        let mut cooling = match self.cooling.take() {
+            Some(cooling) => cooling,
+            _ => return self.propagate(power, temperature, 0),
+        };
+        let (element_count, time_step) = (self.temperature.len(), self.power.time_step());
+        let mut step_power = vec![0.0; element_count + 1];
+        let mut step_temperature = vec![0.0; element_count + 1];
+        for i in 0..(power.len() / element_count) {
+            let range = (i * element_count)..((i + 1) * element_count);
+            step_power[..element_count].copy_from_slice(&power[range.clone()]);
+            step_power[element_count] = cooling.inject(time + i as f64 * time_step);
+            self.propagate(&step_power, &mut step_temperature, 1);
+            temperature[range].copy_from_slice(&step_temperature[..element_count]);
+            cooling.sink = step_temperature[element_count];
+        }
+        self.cooling = Some(cooling);
+    }
+
+    // Advance the thermal circuit given the power of the processing elements
+    // followed by the power of a number of extra units at each step.
+    fn propagate(&mut self, power: &[f64], temperature: &mut [f64], extra_count: usize) {
+        let grid = match self.grid {
+            Some(ref grid) => grid,
+            _ => return self.simulator.next(power, temperature),
+        };
+        let element_count = self.temperature.len();
+        let (unit_count, cell_count) = (element_count + extra_count, grid.cell_count());
+        let node_count = cell_count + extra_count;
+        let step_count = power.len() / unit_count;
+        let mut node_power = vec![0.0; step_count * node_count];
+        for i in 0..step_count {
+            let (power, node_power) = (&power[(i * unit_count)..((i + 1) * unit_count)],
+                                       &mut node_power[(i * node_count)..((i + 1) * node_count)]);
+            grid.distribute(&power[..element_count], &mut node_power[..cell_count]);
+            node_power[cell_count..].copy_from_slice(&power[element_count..]);
+        }
+        let mut node_temperature = vec![0.0; step_count * node_count];
+        self.simulator.next(&node_power, &mut node_temperature);
+        self.cells.reserve(step_count * cell_count);
+        for i in 0..step_count {
+            let (node_temperature, temperature) = (
+                &node_temperature[(i * node_count)..((i + 1) * node_count)],
+                &mut temperature[(i * unit_count)..((i + 1) * unit_count)],
+            );
+            grid.average(&node_temperature[..cell_count], &mut temperature[..element_count]);
+            temperature[element_count..].copy_from_slice(&node_temperature[cell_count..]);
+            self.cells.extend(&node_temperature[..cell_count]);
+        }
+    }
+
+    fn locate(&mut self, data: &mut Data) {
+        let cells = mem::replace(&mut self.cells, vec![]);
+        let grid = match self.grid {
+            Some(ref grid) => grid,
+            _ => return,
+        };
+        let temperature = data.temperature.as_ref().unwrap();
+        let (element_count, cell_count) = (temperature.element_count, grid.cell_count());
+        let mut peak = temperature.clone_zero();
+        let mut probes = Profile {
+            element_count: self.probes.len(),
+            step_count: temperature.step_count,
+            time: temperature.time,
+            time_step: temperature.time_step,
+            data: Vec::with_capacity(temperature.step_count * self.probes.len()),
+        };
+        for i in 0..temperature.step_count {
+            let cells = &cells[(i * cell_count)..((i + 1) * cell_count)];
+            grid.peak(cells, &mut peak[(i * element_count)..((i + 1) * element_count)]);
+            probes.data.extend(self.probes.iter().map(|probe| {
+                grid.interpolate(cells, probe.x, probe.y)
+            }));
+        }
+        data.peak = Some(peak);
+        if !self.probes.is_empty() {
+            data.probes = Some(probes);
+        }
+        data.map = Some(Map {
+            rows: grid.rows,
+            columns: grid.columns,
+            temperature: Profile {
+                element_count: cell_count,
+                step_count: temperature.step_count,
+                time: temperature.time,
+                time_step: temperature.time_step,
+                data: cells,
+            },
+        });
+    }
+
+    fn manage(&mut self) {
+        let &mut Thermal {
+            ref mut power, ref temperature, ref mut dtm, ref mut notices, ..
+        } = self;
+        let dtm = match dtm.as_mut() {
+            Some(dtm) => dtm,
+            _ => return,
+        };
+        let changes = dtm.next(temperature);
+        if changes.is_empty() {
+            return;
+        }
+        let time = power.time();
+        for (element, hot) in changes {
+            if !hot {
+                power.throttle(element, 1.0, 1.0);
+                notices.push(Notice::Unthrottle { time: time, element: element });
+                continue;
+            }
+            let speed = match dtm.policy {
+                DtmPolicy::StopAndGo => 0.0,
+                DtmPolicy::Throttling { speed, power: scale } => {
+                    power.throttle(element, speed, scale);
+                    speed
+                },
+                DtmPolicy::Migration => {
+                    let mut candidates = (0..temperature.len()).filter(|&i| {
+                        !dtm.is_hot(i) && temperature[i].is_finite()
+                    }).collect::<Vec<_>>();
+                    candidates.sort_by(|&i, &j| temperature[i].partial_cmp(&temperature[j])
+                                                              .unwrap());
+                    if power.migrate(element, &candidates) { 1.0 } else { 0.0 }
+                },
+            };
+            if speed == 0.0 {
+                power.throttle(element, 0.0, 0.0);
+            }
+            notices.push(Notice::Throttle { time: time, element: element, speed: speed });
+        }
+        power.replan();
     }
 }
 
 impl Platform for Thermal {
-    type Data = (Profile, Profile);
+    type Data = Data;
 
     #[inline(always)]
     fn elements(&self) -> &[Element] {
-        &self.elements
+        self.power.elements()
     }
 
     fn next(&mut self, time: f64) -> Result<Self::Data> {
-        let power = self.builder.pull(time);
-        let mut temperature = power.clone_zero();
-        self.simulator.next(&power, &mut temperature);
-        Ok((power, temperature))
+        let (power, state, temperature) = if self.dtm.is_some() {
+            self.next_managed(time)
+        } else {
+            self.next_plain(time)
+        };
+        let sensed = self.sensors.as_mut().map(|sensors| sensors.next(&temperature));
+        let damage = match self.reliability {
+            Some(ref mut reliability) => {
+                let damage = reliability.next(&temperature);
+                if reliability.profile { Some(damage) } else { None }
+            },
+            _ => None,
+        };
+        let (current, droop) = self.power.supply(&power);
+        let mut data = Data {
+            power: power,
+            state: state,
+            temperature: Some(temperature),
+            sensed: sensed,
+            peak: None,
+            probes: None,
+            map: None,
+            damage: damage,
+            current: current,
+            droop: droop,
+            metrics: self.power.metrics(),
+        };
+        self.locate(&mut data);
+        Ok(data)
     }
 
+    #[inline]
     fn push(&mut self, job: &Job, start: f64, mapping: &Mapping) -> Result<()> {
-        let (from, onto) = (&job.components, &self.elements);
-        for &(i, j) in mapping {
-            let (from, onto) = (&from[i], &onto[j]);
-            self.builder.push(onto.id, start, job.time_step, &from.dynamic_power);
-        }
-        Ok(())
+        self.power.push(job, start, mapping)
     }
-}
 
-fn construct_power(elements: &[Element], config: &Config) -> Result<ProfileBuilder> {
-    let element_count = elements.len();
-    let time_step = *some!(config.get::<f64>("time_step"), "a time step is required");
-    let path = path!(config, "a leakage pattern is required");
-    info!(target: "Platform", "Modeling leakage power based on {:?}...", &path);
-    let models = try!(Component::collect(path));
-    let mut leakage_power = vec![0.0; element_count];
-    for (i, element) in elements.iter().enumerate() {
-        if let Some(model) = models.iter().find(|model| model.kind == element.kind) {
-            debug_assert!(element.area > 0.0 && model.area > 0.0);
-            leakage_power[i] = (element.area / model.area) * model.leakage_power;
-        } else {
-            raise!("cannot find leakage data for a processing element");
-        }
+    #[inline]
+    fn variation(&self) -> Option<&Variation> {
+        self.variation.as_ref()
+    }
+
+    #[inline]
+    fn reliability(&self) -> Option<&Reliability> {
+        self.reliability.as_ref()
+    }
+
+    fn notices(&mut self) -> Vec<Notice> {
+        let mut notices = mem::replace(&mut self.notices, vec![]);
+        notices.extend(self.power.notices());
+        notices
     }
-    Ok(ProfileBuilder::new(element_count, time_step, leakage_power))
 }
 
-fn construct_temperature(config: &Config) -> Result<(Vec<Element>, Simulator)> {
-    let path = path!(config, "a thermal specification is required");
-    info!(target: "Platform", "Modeling temperature based on {:?}...", &path);
-    let (elements, circuit) = match path.extension() {
-        Some(extension) if extension == "stk" => try!(construct_threed_ice(&path)),
-        _ => raise!("the format of {:?} is unknown", &path),
+fn construct_temperature(classifier: &ElementClassifier, config: &Config,
+                         generator: Option<&Generator>, variation: Option<&Variation>)
+                         -> Result<(Vec<Element>, Simulator, f64, Option<Grid>,
+                                    Option<Cooling>)> {
+    let ambience = *some!(config.get::<f64>("ambience"), "an ambient temperature is required");
+    let exposed = config.branch("conditions").is_some();
+    let (elements, circuit, grid, model) = match generator {
+        Some(generator) => {
+            try!(construct_generated(generator, classifier, config, ambience, variation,
+                                     exposed))
+        },
+        _ => try!(construct_specified(classifier, config, variation, exposed)),
+    };
+    let cooling = match (config.branch("conditions"), model) {
+        (Some(ref branch), Some(model)) => {
+            info!(target: "Platform", "Reading the cooling conditions...");
+            Some(Cooling {
+                conditions: try!(Conditions::new(branch, ambience, model.convection)),
+                ambience: ambience,
+                conductance: model.sink_conductance(),
+                area: model.sink.side * model.sink.side,
+                sink: ambience,
+            })
+        },
+        _ => None,
     };
     info!(target: "Platform", "Found {} processing elements and {} thermal nodes.",
           elements.len(), circuit.capacitance.len());
     info!(target: "Platform", "Initializing the temperature simulator...");
     let config = temperature::Config {
-        ambience: *some!(config.get::<f64>("ambience"), "an ambient temperature is required"),
+        ambience: ambience,
         time_step: *some!(config.get::<f64>("time_step"), "a time step is required"),
     };
     let simulator = ok!(Simulator::new(circuit, config));
-    Ok((elements, simulator))
+    Ok((elements, simulator, ambience, grid, cooling))
 }
 
-fn construct_threed_ice(path: &Path) -> Result<(Vec<Element>, temperature::Circuit)> {
+fn construct_specified(classifier: &ElementClassifier, config: &Config,
+                       variation: Option<&Variation>, exposed: bool)
+                       -> Result<(Vec<Element>, temperature::Circuit, Option<Grid>,
+                                  Option<Compact>)> {
+    let path = path!(config, "a thermal specification is required");
+    info!(target: "Platform", "Modeling temperature based on {:?}...", &path);
+    Ok(match (config.get::<String>("model"), path.extension()) {
+        (Some(model), _) if model == "compact" => {
+            let floorplan = try!(Floorplan::open(&path));
+            let branch = config.branch("compact");
+            let config = branch.as_ref().unwrap_or(config);
+            let mut model = try!(Compact::new(config));
+            if let Some(variation) = variation {
+                variation.perturb(&mut model);
+            }
+            try!(construct_compact(&floorplan, &model, classifier, config, exposed))
+        },
+        _ if exposed => raise!("time-varying conditions require the compact thermal model"),
+        _ if variation.map(|variation| variation.conductivity.is_some()).unwrap_or(false) => {
+            raise!("the variation of conductivity requires the compact thermal model");
+        },
+        (Some(model), _) => raise!("found an unknown thermal model ({:?})", model),
+        (_, Some(extension)) if extension == "flp" => {
+            let (elements, circuit) = try!(construct_hotspot(&path, classifier, config));
+            (elements, circuit, None, None)
+        },
+        (_, Some(extension)) if extension == "stk" => {
+            let (elements, circuit) = try!(construct_threed_ice(&path, classifier));
+            (elements, circuit, None, None)
+        },
+        _ => raise!("the format of {:?} is unknown", &path),
+    })
+}
+
+fn construct_generated(generator: &Generator, classifier: &ElementClassifier, config: &Config,
+                       ambience: f64, variation: Option<&Variation>, exposed: bool)
+                       -> Result<(Vec<Element>, temperature::Circuit, Option<Grid>,
+                                  Option<Compact>)> {
+    info!(target: "Platform", "Generating a platform with {}x{} cores...",
+          generator.rows, generator.columns);
+    let mut generator = generator.clone();
+    if let Some(variation) = variation {
+        variation.perturb(&mut generator.model);
+    }
+    if let Some(ref path) = generator.output {
+        if exposed {
+            raise!("time-varying conditions require the compact thermal model");
+        }
+        info!(target: "Platform", "Writing the generated platform into {:?}...", path);
+        try!(generator.write_threed_ice(path, path.with_extension("flp"), ambience));
+        let (elements, circuit) = try!(construct_threed_ice(path, classifier));
+        return Ok((elements, circuit, None, None));
+    }
+    let branch = config.branch("compact");
+    let config = branch.as_ref().unwrap_or(config);
+    construct_compact(&generator.floorplan(), &generator.model, classifier, config, exposed)
+}
+
+fn construct_compact(floorplan: &Floorplan, model: &Compact, classifier: &ElementClassifier,
+                     config: &Config, exposed: bool)
+                     -> Result<(Vec<Element>, temperature::Circuit, Option<Grid>,
+                                Option<Compact>)> {
+    let elements = try!(floorplan.elements(0, classifier));
+    let circuit = |floorplan: &Floorplan| if exposed {
+        model.circuit_with_sink(floorplan)
+    } else {
+        model.circuit(floorplan)
+    };
+    let nominal = if exposed { Some(model.clone()) } else { None };
+    Ok(match config.branch("grid") {
+        Some(ref config) => {
+            let rows = *some!(config.get::<i64>("rows"), "the number of rows is required");
+            let columns = *some!(config.get::<i64>("columns"),
+                                 "the number of columns is required");
+            if rows <= 0 || columns <= 0 {
+                raise!("the number of rows and columns of a thermal grid should be positive");
+            }
+            let grid = try!(Grid::new(floorplan, rows as usize, columns as usize));
+            let circuit = circuit(&Floorplan { blocks: grid.cells() });
+            (elements, circuit, Some(grid), nominal)
+        },
+        _ => (elements, circuit(floorplan), None, nominal),
+    })
+}
+
+fn construct_hotspot(path: &Path, classifier: &ElementClassifier, config: &Config)
+                     -> Result<(Vec<Element>, temperature::Circuit)> {
+    use temperature::circuit::HotSpot;
+
+    let elements = try!(try!(Floorplan::open(path)).elements(0, classifier));
+    let config = some!(config.branch("hotspot"), "a HotSpot configuration is required");
+    let specification = path!(config["config"], "a HotSpot configuration file is required");
+    Ok((elements, ok!(HotSpot::new(path, &specification))))
+}
+
+fn construct_threed_ice(path: &Path, classifier: &ElementClassifier)
+                        -> Result<(Vec<Element>, temperature::Circuit)> {
     use temperature::circuit::ThreeDICE;
     use threed_ice::{StackElement, System};
 
     let system = ok!(System::new(path));
+    let floorplans = try!(read_threed_ice_floorplans(path));
     let mut elements = vec![];
+    let mut die_count = 0;
     for element in system.stack.elements.iter().rev() {
         let die = match element {
             &StackElement::Die(ref die) => die,
@@ -102,31 +515,77 @@ fn construct_threed_ice(path: &Path) -> Result<(Vec<Element>, temperature::Circu
         };
         for element in die.floorplan.elements.iter() {
             let id = elements.len();
-            let kind = try!(ElementKind::from_str(&element.id));
             let area = 1e-6 * 1e-6 * element.area;
-            elements.push(Element::new(id, kind, area));
+            let mut element = try!(classifier.element(id, &element.id, area, die_count));
+            element.bounds = floorplans.get(die_count).and_then(|floorplan| {
+                floorplan.blocks.iter().find(|block| block.name == element.name)
+            }).map(|block| block.bounds());
+            elements.push(element);
         }
+        die_count += 1;
     }
     Ok((elements, ok!(ThreeDICE::from(&system))))
 }
 
+// Read the floorplans of the dies of a 3D-ICE stack starting from the bottom
+// one, which is listed last in the stack section.
+fn read_threed_ice_floorplans(path: &Path) -> Result<Vec<Floorplan>> {
+    use std::fs::File;
+    use std::io::Read;
+
+    let mut content = String::new();
+    ok!(ok!(File::open(path)).read_to_string(&mut content));
+    let mut floorplans = vec![];
+    for statement in content.split(';') {
+        let statement = statement.trim();
+        if !statement.starts_with("die") || !statement.contains("floorplan") {
+            continue;
+        }
+        let chunks = statement.split('"').collect::<Vec<_>>();
+        if chunks.len() < 3 {
+            raise!("found a malformed die in the stack ({:?})", statement);
+        }
+        floorplans.push(try!(Floorplan::open_threed_ice(chunks[1])));
+    }
+    floorplans.reverse();
+    Ok(floorplans)
+}
+
 #[cfg(test)]
 mod tests {
     use configuration::format::TOML;
-    use platform::{Element, ElementKind, Thermal};
+    use random;
+
+    use platform::{Bounds, Element, ElementCapacity, ElementClassifier, ElementKind, Platform};
+    use platform::Thermal;
 
     #[test]
     fn new() {
-        let config = TOML::open("tests/fixtures/streamer.toml").unwrap()
-                                                               .branch("platform")
-                                                               .unwrap();
-        let platform = Thermal::new(&config).unwrap();
-        assert_eq!(platform.elements, &[
-            Element { id: 0, kind: ElementKind::Core, area: 3976e-6 * 7950e-6 },
-            Element { id: 1, kind: ElementKind::Core, area: 3976e-6 * 7950e-6 },
-            Element { id: 2, kind: ElementKind::Core, area: 3976e-6 * 7950e-6 },
-            Element { id: 3, kind: ElementKind::Core, area: 3976e-6 * 7950e-6 },
-            Element { id: 4, kind: ElementKind::L3, area: 15904e-6 * 3894e-6 },
+        macro_rules! element(
+            ($id:expr, $name:expr, $kind:expr, $capacity:ident,
+             $left:expr, $bottom:expr, $width:expr, $height:expr) => ({
+                let mut element = Element::new($id, $name.to_string(), ElementKind::new($kind),
+                                               ElementCapacity::$capacity, $width * $height, 0);
+                element.bounds = Some(Bounds {
+                    left: $left,
+                    bottom: $bottom,
+                    width: $width,
+                    height: $height,
+                });
+                element
+            });
+        );
+
+        let config = TOML::open("tests/fixtures/streamer.toml").unwrap();
+        let classifier = ElementClassifier::new(&config).unwrap();
+        let platform = Thermal::new(&config.branch("platform").unwrap(), &classifier,
+                                    random::default()).unwrap();
+        assert_eq!(platform.elements(), &[
+            element!(0, "Core0", "core", Single, 0.0, 0.0, 3976e-6, 7950e-6),
+            element!(1, "Core1", "core", Single, 3976e-6, 0.0, 3976e-6, 7950e-6),
+            element!(2, "Core2", "core", Single, 7952e-6, 0.0, 3976e-6, 7950e-6),
+            element!(3, "Core3", "core", Single, 11928e-6, 0.0, 3976e-6, 7950e-6),
+            element!(4, "L30", "l3", Infinite, 0.0, 7950e-6, 15904e-6, 3894e-6),
         ]);
     }
 }
@@ -0,0 +1,143 @@
+use probability::distribution::{Gaussian, Sample};
+
+use {Config, Result, Source};
+use platform::{Compact, Element};
+
+/// A process-variation model.
+///
+/// The leakage power of each processing element is scaled by a log-normal
+/// multiplier with unit mean. The multipliers are either independent or
+/// spatially correlated with an exponential kernel of the distance between
+/// the centers of the processing elements. The conductivity of each layer
+/// of the compact thermal model can be scaled by a log-normal multiplier as
+/// well.
+#[derive(Clone, Debug)]
+pub struct Variation {
+    /// The leakage multipliers of the processing elements.
+    pub leakage: Vec<f64>,
+    /// The conductivity multipliers of the die, the thermal interface, the
+    /// heat spreader, and the heat sink if perturbed.
+    pub conductivity: Option<[f64; 4]>,
+    leakage_sigma: f64,
+    correlation_length: f64,
+}
+
+impl Variation {
+    /// Create a model and sample the conductivity multipliers.
+    pub fn new(config: &Config, source: &mut Source) -> Result<Variation> {
+        macro_rules! get(
+            ($name:expr) => (*config.get::<f64>($name).unwrap_or(&0.0));
+        );
+        let (leakage_sigma, correlation_length) = (get!("leakage_sigma"),
+                                                   get!("correlation_length"));
+        let conductivity_sigma = get!("conductivity_sigma");
+        if leakage_sigma < 0.0 || conductivity_sigma < 0.0 || correlation_length < 0.0 {
+            raise!("the parameters of process variation should be nonnegative");
+        }
+        let conductivity = if conductivity_sigma > 0.0 {
+            let mut conductivity = [0.0; 4];
+            for value in conductivity.iter_mut() {
+                *value = multiplier(conductivity_sigma, standard(source));
+            }
+            Some(conductivity)
+        } else {
+            None
+        };
+        Ok(Variation {
+            leakage: vec![],
+            conductivity: conductivity,
+            leakage_sigma: leakage_sigma,
+            correlation_length: correlation_length,
+        })
+    }
+
+    /// Sample the leakage multipliers of processing elements.
+    pub fn sample(&mut self, elements: &[Element], source: &mut Source) -> Result<()> {
+        let count = elements.len();
+        let mut normal = (0..count).map(|_| standard(source)).collect::<Vec<_>>();
+        if self.leakage_sigma > 0.0 && self.correlation_length > 0.0 {
+            let mut centers = Vec::with_capacity(count);
+            for element in elements {
+                let bounds = some!(element.bounds,
+                                   "correlated variation requires the geometry of {:?}",
+                                   element.name);
+                centers.push((element.die, bounds.left + bounds.width / 2.0,
+                              bounds.bottom + bounds.height / 2.0));
+            }
+            let mut covariance = vec![0.0; count * count];
+            for i in 0..count {
+                for j in 0..count {
+                    let ((die1, x1, y1), (die2, x2, y2)) = (centers[i], centers[j]);
+                    if die1 != die2 {
+                        continue;
+                    }
+                    let distance = ((x1 - x2).powi(2) + (y1 - y2).powi(2)).sqrt();
+                    covariance[i * count + j] = (-distance / self.correlation_length).exp();
+                }
+            }
+            let factor = cholesky(&covariance, count);
+            normal = (0..count).map(|i| {
+                (0..(i + 1)).fold(0.0, |sum, j| sum + factor[i * count + j] * normal[j])
+            }).collect();
+        }
+        self.leakage = normal.iter().map(|&z| multiplier(self.leakage_sigma, z)).collect();
+        Ok(())
+    }
+
+    /// Perturb the materials of a compact thermal model.
+    pub fn perturb(&self, model: &mut Compact) {
+        if let Some(conductivity) = self.conductivity {
+            model.die.conductivity *= conductivity[0];
+            model.interface.conductivity *= conductivity[1];
+            model.spreader.conductivity *= conductivity[2];
+            model.sink.conductivity *= conductivity[3];
+        }
+    }
+}
+
+// Compute the lower-triangular Cholesky factor of a symmetric positive
+// semidefinite matrix, skipping the columns that are numerically zero.
+fn cholesky(matrix: &[f64], count: usize) -> Vec<f64> {
+    let mut factor = vec![0.0; count * count];
+    for j in 0..count {
+        let mut diagonal = matrix[j * count + j];
+        for k in 0..j {
+            diagonal -= factor[j * count + k] * factor[j * count + k];
+        }
+        if diagonal <= 1e-12 {
+            continue;
+        }
+        let diagonal = diagonal.sqrt();
+        factor[j * count + j] = diagonal;
+        for i in (j + 1)..count {
+            let mut value = matrix[i * count + j];
+            for k in 0..j {
+                value -= factor[i * count + k] * factor[j * count + k];
+            }
+            factor[i * count + j] = value / diagonal;
+        }
+    }
+    factor
+}
+
+#[inline]
+fn multiplier(sigma: f64, z: f64) -> f64 {
+    (sigma * z - sigma * sigma / 2.0).exp()
+}
+
+#[inline]
+fn standard(source: &mut Source) -> f64 {
+    Gaussian::new(0.0, 1.0).sample(source)
+}
+
+#[cfg(test)]
+mod tests {
+    use assert;
+
+    #[test]
+    fn cholesky() {
+        let matrix = vec![4.0, 2.0, 2.0, 2.0, 2.0, 1.0, 2.0, 1.0, 3.0];
+        let factor = super::cholesky(&matrix, 3);
+        assert::close(&factor, &[2.0, 0.0, 0.0, 1.0, 1.0, 0.0, 1.0, 0.0, 2f64.sqrt()], 1e-14);
+    }
+}
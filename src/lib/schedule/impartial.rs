@@ -1,12 +1,17 @@
 use std::cmp::Ord;
+use std::f64::INFINITY;
 
 use {Config, Result, Source};
 use math;
 use platform::{Element, Platform};
-use schedule::{Decision, Mapping, NoData, Schedule, Queue};
+use schedule::{Decision, Interval, Mapping, NoData, Schedule, Queue};
 use system::Job;
+use workload::Colocation;
 
 /// A first-in-first-served scheduling policy.
+///
+/// Each component of a job is assigned to the fastest suitable processing
+/// element, and ties are broken in favor of the lower dynamic power.
 pub struct Impartial {
     elements: Vec<Element>,
     queues: Vec<Queue>,
@@ -31,45 +36,45 @@ impl Schedule for Impartial {
     fn next(&mut self, job: &Job) -> Result<Decision> {
         let hosts = &self.elements;
         let guests = &job.components;
-        let (have, need) = (hosts.len(), guests.len());
+        let need = guests.len();
         let guest_order = permute(need, &mut self.source);
+        let groups = job.constraint.partition(hosts);
         let mut start = job.arrival;
-        let length = job.duration();
+        let length = job.duration() / try!(slowest(job, hosts));
         'outer: loop {
             let intervals = self.queues.iter().map(|queue| queue.next(start, length))
                                               .collect::<Vec<_>>();
             let host_order = sort(&intervals);
             start = intervals[host_order[0]].start();
-            let mut found = vec![None; need];
-            let mut taken = vec![false; have];
-            'inner: for &i in &guest_order {
-                for &j in &host_order {
-                    if taken[j] || intervals[j].start() != start {
-                        continue;
-                    }
-                    if guests[i].accept(&hosts[j]) {
-                        found[i] = Some(j);
-                        taken[j] = true;
-                        continue 'inner;
-                    }
+            for group in &groups {
+                let found = match allocate(job, hosts, group, &intervals, &guest_order,
+                                           &host_order, start) {
+                    Some(found) => found,
+                    _ => continue,
+                };
+                start = start.max(math::next_after(job.arrival));
+                let (mut speed, mut power) = (INFINITY, 0.0);
+                for &i in &guest_order {
+                    let factors = guests[i].factors(&hosts[found[i]]);
+                    speed = speed.min(factors.0);
+                    power += factors.1 / need as f64;
                 }
-                for &j in &host_order[1..] {
-                    if intervals[j].start() > start {
-                        start = intervals[j].start();
-                        continue 'outer;
-                    }
+                let finish = start + job.duration() / speed;
+                let mut mapping = Mapping::with_capacity(need);
+                for &i in &guest_order {
+                    let j = found[i];
+                    self.queues[j].push((start, finish));
+                    mapping.push((i, hosts[j].id));
                 }
-                raise!("failed to allocate resources for a job");
+                return Ok(Decision::accept(start, finish, mapping, speed, power));
             }
-            start = start.max(math::next_after(job.arrival));
-            let finish = start + length;
-            let mut mapping = Mapping::with_capacity(need);
-            for &i in &guest_order {
-                let j = some!(found[i]);
-                self.queues[j].push((start, finish));
-                mapping.push((i, hosts[j].id));
+            for &j in &host_order[1..] {
+                if intervals[j].start() > start {
+                    start = intervals[j].start();
+                    continue 'outer;
+                }
             }
-            return Ok(Decision::accept(start, finish, mapping));
+            raise!("failed to allocate resources for a job");
         }
     }
 
@@ -79,6 +84,91 @@ impl Schedule for Impartial {
         }
         Ok(())
     }
+
+    fn reschedule(&mut self, _: &Job, from: &Decision, to: &Decision) -> Result<()> {
+        if let &Decision::Accept { start, finish, ref mapping, .. } = from {
+            for &(_, j) in mapping {
+                self.queues[j].remove((start, finish));
+            }
+        }
+        if let &Decision::Accept { start, finish, ref mapping, .. } = to {
+            if start.is_infinite() {
+                return Ok(());
+            }
+            for &(_, j) in mapping {
+                self.queues[j].push((start, finish));
+            }
+        }
+        Ok(())
+    }
+}
+
+// Assign each component to the fastest suitable processing element available
+// at the given time. When the components have to be placed on adjacent
+// processing elements, each processing element is tried as the host of the
+// first component in turn.
+fn allocate(job: &Job, hosts: &[Element], group: &[bool], intervals: &[Interval],
+            guest_order: &[usize], host_order: &[usize], start: f64) -> Option<Vec<usize>> {
+    if let Colocation::Adjacent = job.constraint.colocation {
+        host_order.iter().filter_map(|&seed| {
+            assign(job, hosts, group, intervals, guest_order, host_order, start, Some(seed))
+        }).next()
+    } else {
+        assign(job, hosts, group, intervals, guest_order, host_order, start, None)
+    }
+}
+
+fn assign(job: &Job, hosts: &[Element], group: &[bool], intervals: &[Interval],
+          guest_order: &[usize], host_order: &[usize], start: f64, seed: Option<usize>)
+          -> Option<Vec<usize>> {
+    let guests = &job.components;
+    let mut found = vec![0; guests.len()];
+    let mut taken = vec![false; hosts.len()];
+    for (k, &i) in guest_order.iter().enumerate() {
+        let placed = guest_order[..k].iter().map(|&i| &hosts[found[i]]).collect::<Vec<_>>();
+        let mut best: Option<(usize, (f64, f64))> = None;
+        for &j in host_order {
+            if taken[j] || !group[j] || intervals[j].start() != start {
+                continue;
+            }
+            if k == 0 && seed.map(|seed| seed != j).unwrap_or(false) {
+                continue;
+            }
+            if guests[i].accept(&hosts[j]) && job.constraint.accept(&hosts[j]) &&
+               job.constraint.admit(&hosts[j], &placed) {
+                let (speed, power) = guests[i].factors(&hosts[j]);
+                if best.map(|(_, other)| {
+                    speed > other.0 || (speed == other.0 && power < other.1)
+                }).unwrap_or(true) {
+                    best = Some((j, (speed, power)));
+                }
+            }
+        }
+        match best {
+            Some((j, _)) => {
+                found[i] = j;
+                taken[j] = true;
+            },
+            _ => return None,
+        }
+    }
+    Some(found)
+}
+
+// Return the speed of a job on the slowest suitable processing elements, which
+// bounds the duration of the job from above.
+fn slowest(job: &Job, hosts: &[Element]) -> Result<f64> {
+    let mut slowest = INFINITY;
+    for guest in &job.components {
+        let speed = hosts.iter().filter(|host| guest.accept(host) && job.constraint.accept(host))
+                                .map(|host| guest.factors(host).0)
+                                .fold(INFINITY, f64::min);
+        if speed.is_infinite() {
+            raise!("found no processing element suitable for the component {:?}", guest.name);
+        }
+        slowest = slowest.min(speed);
+    }
+    Ok(slowest)
 }
 
 fn permute(count: usize, source: &mut Source) -> Vec<usize> {
@@ -101,3 +191,67 @@ fn sort<T: Ord>(items: &[T]) -> Vec<usize> {
     items.sort_by(|one, other| one.1.cmp(&other.1));
     items.iter().map(|item| item.0).collect()
 }
+
+#[cfg(test)]
+mod tests {
+    use assert;
+    use configuration::format::TOML;
+    use random;
+
+    use platform::{ElementClassifier, Power};
+    use schedule::{Decision, Schedule};
+    use system::Job;
+    use workload::Pattern;
+    use super::Impartial;
+
+    #[test]
+    fn next() {
+        let source = r#"
+            root = "tests/fixtures"
+            time_step = 1e-3
+
+            [[kinds]]
+            name = "fast"
+            family = "core"
+            pattern = "core3"
+            performance = 2.0
+            power = 3.0
+
+            [[kinds]]
+            name = "core"
+
+            [[kinds]]
+            name = "l3"
+            capacity = "{}"
+
+            [platform.floorplan]
+            path = "004_hotspot.flp"
+
+            [platform.power]
+            path = "blackscholes.sqlite3"
+
+            [pattern]
+            path = "blackscholes.sqlite3"
+        "#;
+        let construct = |capacity: &str| {
+            let config = TOML::parse(&source.replace("{}", capacity)).unwrap();
+            let classifier = ElementClassifier::new(&config).unwrap();
+            let platform = Power::new(&config.branch("platform").unwrap(), &classifier).unwrap();
+            let pattern = Pattern::new(&config.branch("pattern").unwrap(), &classifier).unwrap();
+            let schedule = Impartial::new(&config, &platform, random::default()).unwrap();
+            (schedule, Job::new(0, 0.0, pattern))
+        };
+
+        let (mut schedule, job) = construct("infinite");
+        match schedule.next(&job).unwrap() {
+            Decision::Accept { speed, power, ref mapping, .. } => {
+                assert!(mapping.iter().any(|&(_, j)| j == 3));
+                assert::close(&[speed, power], &[1.0, (3.0 + 1.0 + 1.0) / 3.0], 1e-12);
+            },
+            _ => unreachable!(),
+        }
+
+        let (mut schedule, job) = construct("zero");
+        assert!(schedule.next(&job).is_err());
+    }
+}
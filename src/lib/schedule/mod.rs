@@ -19,6 +19,13 @@ pub trait Schedule {
 
     /// Advance time and consume the data accumulated since the previous call.
     fn push(&mut self, f64, Self::Data) -> Result<()>;
+
+    /// Account for a change in the execution of a job imposed by the platform.
+    ///
+    /// The default implementation ignores the change.
+    fn reschedule(&mut self, _: &Job, _: &Decision, _: &Decision) -> Result<()> {
+        Ok(())
+    }
 }
 
 /// A scheduling decision.
@@ -31,6 +38,12 @@ pub enum Decision {
         finish: f64,
         /// The mapping of the job to the platform.
         mapping: Mapping,
+        /// The relative speed of the job given the mapping, which is the one
+        /// of its slowest component.
+        speed: f64,
+        /// The relative dynamic power of the job given the mapping averaged
+        /// over its components.
+        power: f64,
     },
     Reject,
 }
@@ -46,8 +59,15 @@ pub struct NoData;
 impl Decision {
     /// Create an accept decision.
     #[inline]
-    pub fn accept(start: f64, finish: f64, mapping: Mapping) -> Decision {
-        Decision::Accept { start: start, finish: finish, mapping: mapping }
+    pub fn accept(start: f64, finish: f64, mapping: Mapping, speed: f64, power: f64)
+                  -> Decision {
+        Decision::Accept {
+            start: start,
+            finish: finish,
+            mapping: mapping,
+            speed: speed,
+            power: power,
+        }
     }
 
     /// Create a reject decision.
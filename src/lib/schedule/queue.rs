@@ -30,12 +30,21 @@ impl Queue {
 
     /// Find a vacant time interval.
     pub fn next(&self, from: f64, length: f64) -> Interval {
-        if let ElementCapacity::Infinite = self.capacity {
-             return Interval(from, INFINITY);
-        }
-        match self.holes(from).find(|&Interval(start, finish)| start + length <= finish) {
+        let found = match self.capacity {
+            ElementCapacity::Zero => return Interval(INFINITY, INFINITY),
+            ElementCapacity::Single => {
+                self.holes(from).find(|&Interval(start, finish)| start + length <= finish)
+            },
+            ElementCapacity::Finite(limit) => {
+                let saturated = self.saturated(limit);
+                let mut holes = Holes { from: from, inner: saturated.iter() };
+                holes.find(|&Interval(start, finish)| start + length <= finish)
+            },
+            ElementCapacity::Infinite => return Interval(from, INFINITY),
+        };
+        match found {
             Some(interval) => interval,
-            _ => unreachable!(),
+            _ => Interval(INFINITY, INFINITY),
         }
     }
 
@@ -48,6 +57,26 @@ impl Queue {
         self.occupied.insert(Interval(start, start.max(finish)));
     }
 
+    /// Release a time interval occupied previously.
+    ///
+    /// Since `push` nudges the start of an interval by at most one unit in the
+    /// last place per occupied interval, the intervals starting further away
+    /// from the given start are not considered.
+    pub fn remove(&mut self, (start, finish): (f64, f64)) {
+        debug_assert!(0.0 <= start);
+        let (base, count) = (start.to_bits(), self.occupied.len() as u64);
+        let nudged = |interval: &&Interval| {
+            interval.start().to_bits().saturating_sub(base) <= count
+        };
+        let found = self.occupied.range(Interval(start, 0.0)..)
+                                 .take_while(nudged)
+                                 .find(|interval| interval.finish() == start.max(finish))
+                                 .cloned();
+        if let Some(interval) = found {
+            self.occupied.remove(&interval);
+        }
+    }
+
     /// Advance time.
     pub fn tick(&mut self, time: f64) {
         let mut redundant = vec![];
@@ -66,6 +95,31 @@ impl Queue {
     fn holes(&self, from: f64) -> Holes {
         Holes { from: from, inner: self.occupied.iter() }
     }
+
+    fn saturated(&self, limit: usize) -> BTreeSet<Interval> {
+        use std::cmp::Ordering;
+
+        let mut events = Vec::with_capacity(2 * self.occupied.len());
+        for &Interval(start, finish) in &self.occupied {
+            events.push((start, 1));
+            events.push((finish, -1));
+        }
+        events.sort_by(|one, other| match one.0.partial_cmp(&other.0) {
+            Some(Ordering::Equal) | None => one.1.cmp(&other.1),
+            Some(ordering) => ordering,
+        });
+        let mut saturated = BTreeSet::new();
+        let (mut count, mut from) = (0isize, 0.0);
+        for (time, change) in events {
+            count += change;
+            if change > 0 && count == limit as isize {
+                from = time;
+            } else if change < 0 && count == limit as isize - 1 && from < time {
+                saturated.insert(Interval(from, time));
+            }
+        }
+        saturated
+    }
 }
 
 impl<'l> Iterator for Holes<'l> {
@@ -152,6 +206,61 @@ mod tests {
         test!(queue, 0.0, [(0.0, 1.0), (4.0, INFINITY)]);
     }
 
+    #[test]
+    fn push_finite() {
+        let mut queue = Queue::new(ElementCapacity::Finite(2));
+
+        queue.push((0.0, 10.0));
+        assert_eq!(queue.next(0.0, 5.0), Interval(0.0, INFINITY));
+
+        queue.push((5.0, 15.0));
+        assert_eq!(queue.next(0.0, 5.0), Interval(0.0, 5.0));
+        assert_eq!(queue.next(0.0, 6.0), Interval(10.0, INFINITY));
+
+        queue.push((10.0, 20.0));
+        assert_eq!(queue.next(0.0, 6.0), Interval(15.0, INFINITY));
+
+        queue.push((12.0, 13.0));
+        assert_eq!(queue.saturated(2).into_iter().collect::<Vec<_>>(),
+                   vec![Interval(5.0, 10.0), Interval(10.0, 15.0)]);
+        assert_eq!(queue.saturated(3).into_iter().collect::<Vec<_>>(),
+                   vec![Interval(12.0, 13.0)]);
+    }
+
+    #[test]
+    fn remove() {
+        let mut queue = Queue::new(ElementCapacity::Single);
+
+        queue.push((10.0, 15.0));
+        queue.push((10.0, 20.0));
+        queue.push((25.0, INFINITY));
+        assert_eq!(queue.next(0.0, 20.0), Interval(INFINITY, INFINITY));
+
+        queue.remove((10.0, 20.0));
+        test!(queue, 0.0, [(0.0, 10.0), (15.0, 25.0)]);
+
+        queue.remove((25.0, INFINITY));
+        queue.remove((30.0, 35.0));
+        test!(queue, 0.0, [(0.0, 10.0), (15.0, INFINITY)]);
+    }
+
+    #[test]
+    fn remove_absent() {
+        let mut queue = Queue::new(ElementCapacity::Single);
+
+        queue.push((15.0, 20.0));
+        queue.remove((12.0, 20.0));
+        assert_eq!(queue.occupied.len(), 1);
+
+        queue.push((15.0, 25.0));
+        queue.push((15.0, 20.0));
+        queue.remove((15.0, 20.0));
+        assert_eq!(queue.occupied.len(), 2);
+        queue.remove((15.0, 20.0));
+        assert_eq!(queue.occupied.len(), 1);
+        test!(queue, 0.0, [(0.0, 15.0), (25.0, INFINITY)]);
+    }
+
     #[test]
     fn tick() {
         let mut queue = Queue::new(ElementCapacity::Single);
@@ -2,6 +2,7 @@
 
 extern crate configuration;
 extern crate fractal;
+extern crate matrix;
 extern crate probability;
 extern crate random;
 extern crate sql;
@@ -14,6 +15,7 @@ extern crate threed_ice;
 
 #[macro_use] mod macros;
 
+mod list;
 mod math;
 mod result;
 
@@ -21,6 +21,10 @@ pub enum EventKind {
     Start(Job, Mapping),
     /// A job has finished.
     Finish(Job, Mapping),
+    /// A processing element has been throttled to a relative speed.
+    Throttle(usize, f64),
+    /// A processing element has been unthrottled.
+    Unthrottle(usize),
 }
 
 impl Event {
@@ -41,4 +45,16 @@ impl Event {
     pub fn finish(time: f64, job: Job, mapping: Mapping) -> Event {
         Event { time: time, kind: EventKind::Finish(job, mapping) }
     }
+
+    /// Create a throttle event.
+    #[inline]
+    pub fn throttle(time: f64, element: usize, speed: f64) -> Event {
+        Event { time: time, kind: EventKind::Throttle(element, speed) }
+    }
+
+    /// Create an unthrottle event.
+    #[inline]
+    pub fn unthrottle(time: f64, element: usize) -> Event {
+        Event { time: time, kind: EventKind::Unthrottle(element) }
+    }
 }
@@ -9,6 +9,10 @@ pub struct History {
     pub started: usize,
     /// The number of finished jobs.
     pub finished: usize,
+    /// The number of throttle events.
+    pub throttled: usize,
+    /// The number of unthrottle events.
+    pub unthrottled: usize,
 }
 
 impl History {
@@ -18,6 +22,8 @@ impl History {
             &EventKind::Arrive(..) => self.arrived += 1,
             &EventKind::Start(..) => self.started += 1,
             &EventKind::Finish(..) => self.finished += 1,
+            &EventKind::Throttle(..) => self.throttled += 1,
+            &EventKind::Unthrottle(..) => self.unthrottled += 1,
         }
     }
 }
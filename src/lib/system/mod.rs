@@ -1,9 +1,10 @@
 //! System simulation.
 
-use std::collections::BinaryHeap;
+use std::collections::{BinaryHeap, HashMap, HashSet};
+use std::mem;
 
 use Result;
-use platform::Platform;
+use platform::{Notice, Platform};
 use schedule::{Decision, Schedule};
 use traffic::Traffic;
 use workload::Workload;
@@ -24,6 +25,14 @@ pub struct System<T, W, P, S> {
     schedule: S,
     history: History,
     queue: BinaryHeap<Event>,
+    scheduled: HashMap<usize, Scheduled>,
+    parked: HashMap<usize, Scheduled>,
+}
+
+struct Scheduled {
+    job: Job,
+    decision: Decision,
+    started: bool,
 }
 
 impl<T, W, P, S> System<T, W, P, S>
@@ -38,20 +47,24 @@ impl<T, W, P, S> System<T, W, P, S>
             schedule: schedule,
             history: History::default(),
             queue: BinaryHeap::new(),
+            scheduled: HashMap::new(),
+            parked: HashMap::new(),
         })
     }
 
     /// Advance to the next event and return the data accumulated since the
     /// previous call.
     pub fn next(&mut self) -> Result<Option<(Event, P::Data)>> {
-        match (try!(self.traffic.peek()), self.queue.peek().map(|event| &event.time)) {
-            (Some(&traffic), Some(&queue)) => if traffic < queue {
-                self.next_from_traffic()
-            } else {
-                self.next_from_queue()
-            },
-            (Some(_), None) => self.next_from_traffic(),
-            (None, Some(_)) => self.next_from_queue(),
+        let time = match try!(self.peek()) {
+            Some((time, _)) => time,
+            _ => return Ok(None),
+        };
+        let data = try!(self.platform.next(time));
+        try!(self.schedule.push(time, (&data).into()));
+        try!(self.adjust());
+        match try!(self.peek()) {
+            Some((_, true)) => self.next_from_traffic(data),
+            Some((_, false)) => self.next_from_queue(data),
             _ => Ok(None),
         }
     }
@@ -68,27 +81,139 @@ impl<T, W, P, S> System<T, W, P, S>
         &self.history
     }
 
-    fn next_from_traffic(&mut self) -> Result<Option<(Event, P::Data)>> {
+    fn peek(&mut self) -> Result<Option<(f64, bool)>> {
+        Ok(match (try!(self.traffic.peek()), self.queue.peek().map(|event| &event.time)) {
+            (Some(&traffic), Some(&queue)) => if traffic < queue {
+                Some((traffic, true))
+            } else {
+                Some((queue, false))
+            },
+            (Some(&traffic), None) => Some((traffic, true)),
+            (None, Some(&queue)) => Some((queue, false)),
+            _ => None,
+        })
+    }
+
+    fn next_from_traffic(&mut self, data: P::Data) -> Result<Option<(Event, P::Data)>> {
         let time = some!(try!(self.traffic.next()));
         let pattern = try!(self.workload.next(time));
         let job = Job::new(self.history.arrived, time, pattern);
         let event = Event::arrive(time, job.clone());
         self.history.count(&event);
-        let data = try!(self.platform.next(time));
-        try!(self.schedule.push(time, (&data).into()));
-        if let Decision::Accept { start, finish, mapping } = try!(self.schedule.next(&job)) {
-            try!(self.platform.push(&job, start, &mapping));
-            self.queue.push(Event::start(start, job.clone(), mapping.clone()));
-            self.queue.push(Event::finish(finish, job.clone(), mapping));
+        let decision = try!(self.schedule.next(&job));
+        if let Decision::Accept { start, finish, ref mapping, .. } = decision {
+            self.enqueue(Event::start(start, job.clone(), mapping.clone()));
+            self.enqueue(Event::finish(finish, job.clone(), mapping.clone()));
+            self.track(Scheduled { job: job.clone(), decision: decision.clone(), started: false });
+            try!(self.platform.push(&job, start, mapping));
+            try!(self.adjust());
         }
         Ok(Some((event, data)))
     }
 
-    fn next_from_queue(&mut self) -> Result<Option<(Event, P::Data)>> {
+    fn next_from_queue(&mut self, data: P::Data) -> Result<Option<(Event, P::Data)>> {
         let event = some!(self.queue.pop());
         self.history.count(&event);
-        let data = try!(self.platform.next(event.time));
-        try!(self.schedule.push(event.time, (&data).into()));
+        match &event.kind {
+            &EventKind::Start(ref job, _) => {
+                if let Some(scheduled) = self.scheduled.get_mut(&job.id) {
+                    scheduled.started = true;
+                } else if let Some(scheduled) = self.parked.get_mut(&job.id) {
+                    scheduled.started = true;
+                }
+            },
+            &EventKind::Finish(ref job, _) => {
+                self.scheduled.remove(&job.id);
+            },
+            _ => {},
+        }
         Ok(Some((event, data)))
     }
+
+    // Account for the notices of the platform. The queue is rebuilt once for
+    // all the jobs whose execution has changed.
+    fn adjust(&mut self) -> Result<()> {
+        let (mut order, mut adjusted) = (vec![], HashSet::new());
+        for notice in self.platform.notices() {
+            let (id, start, finish, mapping) = match notice {
+                Notice::Throttle { time, element, speed } => {
+                    self.queue.push(Event::throttle(time, element, speed));
+                    continue;
+                },
+                Notice::Unthrottle { time, element } => {
+                    self.queue.push(Event::unthrottle(time, element));
+                    continue;
+                },
+                Notice::Reschedule { job, start, finish, mapping } => {
+                    (job, start, finish, mapping)
+                },
+            };
+            let mut scheduled = match self.scheduled.remove(&id) {
+                Some(scheduled) => scheduled,
+                _ => match self.parked.remove(&id) {
+                    Some(scheduled) => scheduled,
+                    _ => continue,
+                },
+            };
+            let to = match scheduled.decision {
+                Decision::Accept { speed, power, .. } => {
+                    Decision::accept(start, finish, mapping, speed, power)
+                },
+                Decision::Reject => continue,
+            };
+            try!(self.schedule.reschedule(&scheduled.job, &scheduled.decision, &to));
+            scheduled.decision = to;
+            self.track(scheduled);
+            if adjusted.insert(id) {
+                order.push(id);
+            }
+        }
+        if adjusted.is_empty() {
+            return Ok(());
+        }
+        let queue = mem::replace(&mut self.queue, BinaryHeap::new());
+        self.queue = queue.into_iter().filter(|event| match &event.kind {
+            &EventKind::Start(ref job, _) | &EventKind::Finish(ref job, _) => {
+                !adjusted.contains(&job.id)
+            },
+            _ => true,
+        }).collect();
+        for id in order {
+            let (job, decision, started) = {
+                let scheduled = match self.scheduled.get(&id) {
+                    Some(scheduled) => scheduled,
+                    _ => &self.parked[&id],
+                };
+                (scheduled.job.clone(), scheduled.decision.clone(), scheduled.started)
+            };
+            if let Decision::Accept { start, finish, mapping, .. } = decision {
+                if !started {
+                    self.enqueue(Event::start(start, job.clone(), mapping.clone()));
+                }
+                self.enqueue(Event::finish(finish, job, mapping));
+            }
+        }
+        Ok(())
+    }
+
+    // Keep track of a job that has been accepted. A job that is not expected
+    // to finish, which is the case when its processing elements are stopped,
+    // is parked until the platform reschedules it.
+    fn track(&mut self, scheduled: Scheduled) {
+        let id = scheduled.job.id;
+        match scheduled.decision {
+            Decision::Accept { finish, .. } if finish.is_finite() => {
+                self.scheduled.insert(id, scheduled);
+            },
+            _ => {
+                self.parked.insert(id, scheduled);
+            },
+        }
+    }
+
+    fn enqueue(&mut self, event: Event) {
+        if event.time.is_finite() {
+            self.queue.push(event);
+        }
+    }
 }
@@ -3,40 +3,66 @@ use std::collections::HashMap;
 use std::path::Path;
 
 use Result;
-use platform::{self, ElementKind};
+use platform::{self, ElementCapacity, ElementClassifier, ElementKind};
 
 /// A component of a workload pattern.
 #[derive(Clone, Debug)]
 pub struct Component {
+    /// The name.
+    pub name: String,
     /// The type.
     pub kind: ElementKind,
+    /// The family of the type.
+    pub family: ElementKind,
+    /// The relative performance of the type.
+    pub performance: f64,
+    /// The relative dynamic power of the type.
+    pub power: f64,
     /// The area.
     pub area: f64,
     /// The leakage power.
     pub leakage_power: f64,
     /// The dynamic power.
     pub dynamic_power: Vec<f64>,
+    /// The other dynamic metrics, such as performance counters, sampled at
+    /// the same time moments as the dynamic power.
+    pub metrics: HashMap<String, Vec<f64>>,
 }
 
 impl Component {
     /// Read workload components from a database.
-    pub fn collect<T: AsRef<Path>>(path: T) -> Result<Vec<Component>> {
+    ///
+    /// Apart from `dynamic_power`, the columns of the `dynamic` table listed in
+    /// `metrics` are read as metrics; the other columns are ignored.
+    pub fn collect<T: AsRef<Path>>(path: T, classifier: &ElementClassifier, metrics: &[String])
+                                   -> Result<Vec<Component>> {
         let backend = ok!(Connection::open(path));
         let mut names = try!(read_names(&backend));
         let mut areas = try!(read_static(&backend, "area"));
         let mut leakage_power = try!(read_static(&backend, "leakage_power"));
-        let mut dynamic_power = try!(read_dynamic(&backend, "dynamic_power"));
+        let mut columns = vec!["dynamic_power".to_string()];
+        columns.extend(metrics.iter().cloned());
+        let mut dynamic = try!(read_dynamic(&backend, &columns));
         let mut ids = names.keys().map(|&id| id).collect::<Vec<_>>();
         ids.sort();
         let mut components = vec![];
         for id in ids {
+            let name = names.remove(&id).unwrap();
+            let mut metrics = some!(dynamic.remove(&id),
+                                    "cannot find the dynamic data of a processing element");
+            let dynamic_power = metrics.remove(0);
+            let class = try!(classifier.classify(&name));
             components.push(Component {
-                kind: try!(names.remove(&id).unwrap().parse()),
+                kind: class.kind.clone(),
+                family: class.family.clone(),
+                performance: class.performance,
+                power: class.power,
+                name: name,
                 area: some!(areas.remove(&id), "cannot find the area of a processing element"),
                 leakage_power: some!(leakage_power.remove(&id),
                                      "cannot find the leakage power of a processing element"),
-                dynamic_power: some!(dynamic_power.remove(&id),
-                                     "cannot find the dynamic power of a processing element"),
+                dynamic_power: dynamic_power,
+                metrics: columns[1..].iter().cloned().zip(metrics).collect(),
             });
         }
 
@@ -47,7 +73,14 @@ impl Component {
     /// workload component.
     #[inline]
     pub fn accept(&self, element: &platform::Element) -> bool {
-        self.kind == element.kind
+        self.family == element.family && element.capacity != ElementCapacity::Zero
+    }
+
+    /// Return the factors by which the speed and the dynamic power of this
+    /// workload component are scaled when it runs on a processing element.
+    #[inline]
+    pub fn factors(&self, element: &platform::Element) -> (f64, f64) {
+        (element.performance / self.performance, element.power / self.power)
     }
 }
 
@@ -83,18 +116,25 @@ fn read_static(backend: &Connection, name: &str) -> Result<HashMap<i64, f64>> {
     Ok(data)
 }
 
-fn read_dynamic(backend: &Connection, name: &str) -> Result<HashMap<i64, Vec<f64>>> {
+// Read a number of columns of the dynamic table and return one vector per
+// column for each component.
+fn read_dynamic(backend: &Connection, names: &[String]) -> Result<HashMap<i64, Vec<Vec<f64>>>> {
     use sql::prelude::*;
 
     let mut data = HashMap::new();
-    let statement = select_from("dynamic").columns(&["time", "component_id", name])
+    let mut columns = vec!["time", "component_id"];
+    columns.extend(names.iter().map(|name| &name[..]));
+    let statement = select_from("dynamic").columns(&columns)
                                           .order_by(column("time").ascend());
     let mut cursor = ok!(backend.prepare(ok!(statement.compile()))).cursor();
     while let Some(row) = ok!(cursor.next()) {
-        if let (Some(id), Some(value)) = (row[1].as_integer(), row[2].as_float()) {
-            data.entry(id).or_insert_with(|| vec![]).push(value);
-        } else {
-            raise!("failed to read the {} column", name);
+        let id = some!(row[1].as_integer(), "failed to read the component_id column");
+        let values = data.entry(id).or_insert_with(|| vec![vec![]; names.len()]);
+        for (i, name) in names.iter().enumerate() {
+            let value = row[2 + i].as_float().or_else(|| row[2 + i].as_integer().map(|value| {
+                value as f64
+            }));
+            values[i].push(some!(value, "failed to read the {} column", name));
         }
     }
     Ok(data)
@@ -105,6 +145,31 @@ mod tests {
     use assert;
     use sqlite::Connection;
 
+    use Config;
+    use platform::ElementClassifier;
+    use workload::Component;
+
+    #[test]
+    fn collect() {
+        let classifier = ElementClassifier::new(&Config::new()).unwrap();
+        let path = "tests/fixtures/metrics.sqlite3";
+
+        let components = Component::collect(path, &classifier, &[]).unwrap();
+        assert_eq!(components.len(), 2);
+        assert_eq!(components[0].dynamic_power, vec![1.0, 2.0]);
+        assert!(components[0].metrics.is_empty());
+
+        let metrics = vec!["misses".to_string(), "hit_rate".to_string()];
+        let components = Component::collect(path, &classifier, &metrics).unwrap();
+        assert_eq!(components[1].name, "l30");
+        assert_eq!(components[1].dynamic_power, vec![0.5, 0.25]);
+        assert_eq!(components[1].metrics.len(), 2);
+        assert_eq!(components[1].metrics["misses"], vec![10.0, 20.0]);
+        assert_eq!(components[1].metrics["hit_rate"], vec![0.25, 0.75]);
+
+        assert!(Component::collect(path, &classifier, &["note".to_string()]).is_err());
+    }
+
     #[test]
     fn read_names() {
         let backend = open();
@@ -119,15 +184,16 @@ mod tests {
     #[test]
     fn read_dynamic() {
         let backend = open();
-        let data = super::read_dynamic(&backend, "dynamic_power").unwrap();
+        let data = super::read_dynamic(&backend, &["dynamic_power".to_string()]).unwrap();
 
         assert_eq!(data.len(), 2 + 1);
         for (_, data) in &data {
-            assert_eq!(data.len(), 69);
+            assert_eq!(data.len(), 1);
+            assert_eq!(data[0].len(), 69);
         }
-        assert::close(&[data.get(&0).unwrap()[2]], &[0.60806580312727], 1e-14);
-        assert::close(&[data.get(&1).unwrap()[4]], &[8.68983889250007], 1e-14);
-        assert::close(&[data.get(&2).unwrap()[0]], &[0.00620192518435], 1e-14);
+        assert::close(&[data.get(&0).unwrap()[0][2]], &[0.60806580312727], 1e-14);
+        assert::close(&[data.get(&1).unwrap()[0][4]], &[8.68983889250007], 1e-14);
+        assert::close(&[data.get(&2).unwrap()[0][0]], &[0.00620192518435], 1e-14);
     }
 
     #[test]
@@ -0,0 +1,262 @@
+use {Config, Result};
+use list;
+use platform::Element;
+
+/// A set of placement constraints of a workload pattern.
+#[derive(Clone, Debug, Default)]
+pub struct Constraint {
+    /// The minimum area of a hosting processing element.
+    pub area: Option<f64>,
+    /// The processing elements allowed to host components.
+    pub elements: Option<Selection>,
+    /// The dies allowed to host components.
+    pub dies: Option<Vec<usize>>,
+    /// The co-location rule.
+    pub colocation: Colocation,
+}
+
+/// A co-location rule.
+#[derive(Clone, Debug)]
+pub enum Colocation {
+    /// Components can be placed anywhere.
+    None,
+    /// Components have to be placed on the same die.
+    Die,
+    /// Components have to be placed on processing elements that form a
+    /// connected set with respect to adjacency, which requires the bounding
+    /// boxes of the processing elements to be known.
+    Adjacent,
+    /// Components have to be placed within one of the groups.
+    Groups(Vec<Selection>),
+}
+
+/// A selection of processing elements by identifiers or names.
+///
+/// The identifiers and names are given as comma-separated strings or as arrays
+/// of tables with `id` and `name` keys, respectively.
+#[derive(Clone, Debug, Default)]
+pub struct Selection {
+    /// The identifiers.
+    pub ids: Vec<usize>,
+    /// The names.
+    pub names: Vec<String>,
+}
+
+impl Constraint {
+    /// Create a set of constraints.
+    pub fn new(config: &Config) -> Result<Constraint> {
+        let colocation = match config.get::<String>("colocation") {
+            Some(name) if name == "die" => Colocation::Die,
+            Some(name) if name == "adjacent" => Colocation::Adjacent,
+            Some(name) if name == "none" => Colocation::None,
+            Some(name) => raise!("found an unknown co-location rule ({:?})", name),
+            _ => match config.forest("groups") {
+                Some(ref configs) => {
+                    let mut groups = vec![];
+                    for config in configs {
+                        groups.push(try!(Selection::new(config)));
+                    }
+                    Colocation::Groups(groups)
+                },
+                _ => Colocation::None,
+            },
+        };
+        let dies = try!(list::read::<i64>(config, "dies", "die"));
+        if dies.as_ref().map_or(false, |dies| dies.iter().any(|&die| die < 0)) {
+            raise!("the dies of a constraint should be nonnegative");
+        }
+        Ok(Constraint {
+            area: config.get::<f64>("min_area").map(|&area| area),
+            elements: match config.branch("elements") {
+                Some(ref config) => Some(try!(Selection::new(config))),
+                _ => None,
+            },
+            dies: dies.map(|dies| dies.iter().map(|&die| die as usize).collect()),
+            colocation: colocation,
+        })
+    }
+
+    /// Check if a processing element satisfies the constraints.
+    pub fn accept(&self, element: &Element) -> bool {
+        if let Some(area) = self.area {
+            if element.area < area {
+                return false;
+            }
+        }
+        if let Some(ref selection) = self.elements {
+            if !selection.contains(element) {
+                return false;
+            }
+        }
+        if let Some(ref dies) = self.dies {
+            if !dies.contains(&element.die) {
+                return false;
+            }
+        }
+        true
+    }
+
+    /// Check if a processing element can host a component given the processing
+    /// elements already hosting the other components of the same job.
+    pub fn admit(&self, element: &Element, taken: &[&Element]) -> bool {
+        match &self.colocation {
+            &Colocation::Adjacent => {
+                taken.is_empty() || taken.iter().any(|other| other.is_adjacent(element))
+            },
+            _ => true,
+        }
+    }
+
+    /// Split processing elements into groups such that the components of a
+    /// job have to be placed within one of them.
+    pub fn partition(&self, elements: &[Element]) -> Vec<Vec<bool>> {
+        match &self.colocation {
+            &Colocation::None => vec![vec![true; elements.len()]],
+            &Colocation::Die | &Colocation::Adjacent => {
+                let mut dies = elements.iter().map(|element| element.die).collect::<Vec<_>>();
+                dies.sort();
+                dies.dedup();
+                dies.iter().map(|&die| {
+                    elements.iter().map(|element| element.die == die).collect()
+                }).collect()
+            },
+            &Colocation::Groups(ref groups) => groups.iter().map(|group| {
+                elements.iter().map(|element| group.contains(element)).collect()
+            }).collect(),
+        }
+    }
+}
+
+impl Default for Colocation {
+    #[inline]
+    fn default() -> Colocation {
+        Colocation::None
+    }
+}
+
+impl Selection {
+    /// Create a selection.
+    pub fn new(config: &Config) -> Result<Selection> {
+        let ids = match try!(list::read::<i64>(config, "ids", "id")) {
+            Some(ids) => {
+                if ids.iter().any(|&id| id < 0) {
+                    raise!("the identifiers of a selection should be nonnegative");
+                }
+                ids.iter().map(|&id| id as usize).collect()
+            },
+            _ => vec![],
+        };
+        let names = match try!(list::read::<String>(config, "names", "name")) {
+            Some(names) => names.iter().map(|name| name.to_lowercase()).collect(),
+            _ => vec![],
+        };
+        if ids.is_empty() && names.is_empty() {
+            raise!("found an empty selection of processing elements");
+        }
+        Ok(Selection { ids: ids, names: names })
+    }
+
+    /// Check if a processing element is selected.
+    pub fn contains(&self, element: &Element) -> bool {
+        self.ids.contains(&element.id) || self.names.contains(&element.name.to_lowercase())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use configuration::format::TOML;
+    use platform::{Bounds, Element, ElementCapacity, ElementKind};
+    use super::{Colocation, Constraint, Selection};
+
+    #[test]
+    fn accept() {
+        let elements = elements();
+        let constraint = Constraint {
+            area: Some(1.5),
+            elements: Some(Selection { ids: vec![0], names: vec!["core2".to_string()] }),
+            dies: Some(vec![0]),
+            colocation: Colocation::None,
+        };
+        let accepted = elements.iter().map(|element| constraint.accept(element))
+                                      .collect::<Vec<_>>();
+        assert_eq!(accepted, vec![false, false, true, false]);
+    }
+
+    #[test]
+    fn admit() {
+        let mut elements = elements();
+        for (i, element) in elements.iter_mut().enumerate() {
+            element.bounds = Some(Bounds {
+                left: i as f64, bottom: 0.0, width: 1.0, height: 1.0,
+            });
+        }
+        let constraint = Constraint { colocation: Colocation::Adjacent, ..Constraint::default() };
+        assert!(constraint.admit(&elements[2], &[]));
+        assert!(constraint.admit(&elements[1], &[&elements[0]]));
+        assert!(!constraint.admit(&elements[2], &[&elements[0]]));
+        assert!(constraint.admit(&elements[2], &[&elements[0], &elements[1]]));
+    }
+
+    #[test]
+    fn new() {
+        let config = TOML::open("tests/fixtures/constraint.toml").unwrap();
+        let configs = config.forest("patterns").unwrap();
+
+        let constraint = Constraint::new(&configs[0].branch("constraint").unwrap()).unwrap();
+        let selection = constraint.elements.as_ref().unwrap();
+        assert_eq!(selection.ids, vec![0, 3]);
+        assert_eq!(selection.names, vec!["core1".to_string(), "core2".to_string()]);
+        assert_eq!(constraint.dies, Some(vec![0]));
+        match constraint.colocation {
+            Colocation::Adjacent => {},
+            _ => unreachable!(),
+        }
+
+        let constraint = Constraint::new(&configs[1].branch("constraint").unwrap()).unwrap();
+        assert_eq!(constraint.elements.as_ref().unwrap().ids, vec![1, 2]);
+        assert_eq!(constraint.dies, Some(vec![0, 1]));
+        match constraint.colocation {
+            Colocation::Groups(ref groups) => {
+                assert_eq!(groups.len(), 2);
+                assert_eq!(groups[0].ids, vec![0, 1]);
+                assert_eq!(groups[1].names, vec!["l30".to_string()]);
+            },
+            _ => unreachable!(),
+        }
+
+        assert!(Constraint::new(&TOML::parse("dies = \"0, -1\"").unwrap()).is_err());
+        assert!(Constraint::new(&TOML::parse("elements = { ids = -1 }").unwrap()).is_err());
+    }
+
+    #[test]
+    fn partition() {
+        let elements = elements();
+        let constraint = Constraint { colocation: Colocation::Die, ..Constraint::default() };
+        assert_eq!(constraint.partition(&elements), vec![
+            vec![true, true, true, false],
+            vec![false, false, false, true],
+        ]);
+        let constraint = Constraint {
+            colocation: Colocation::Groups(vec![
+                Selection { ids: vec![0, 3], names: vec![] },
+                Selection { ids: vec![], names: vec!["core1".to_string()] },
+            ]),
+            ..Constraint::default()
+        };
+        assert_eq!(constraint.partition(&elements), vec![
+            vec![true, false, false, true],
+            vec![false, true, false, false],
+        ]);
+    }
+
+    fn elements() -> Vec<Element> {
+        let (core, l3) = (ElementKind::new("core"), ElementKind::new("l3"));
+        let (single, infinite) = (ElementCapacity::Single, ElementCapacity::Infinite);
+        vec![
+            Element::new(0, "Core0".to_string(), core.clone(), single, 1.0, 0),
+            Element::new(1, "Core1".to_string(), core.clone(), single, 2.0, 0),
+            Element::new(2, "Core2".to_string(), core.clone(), single, 2.0, 0),
+            Element::new(3, "L30".to_string(), l3, infinite, 4.0, 1),
+        ]
+    }
+}
@@ -3,10 +3,12 @@
 use Result;
 
 mod component;
+mod constraint;
 mod pattern;
 mod random;
 
 pub use self::component::Component;
+pub use self::constraint::{Colocation, Constraint, Selection};
 pub use self::pattern::{Content, Pattern};
 pub use self::random::Random;
 
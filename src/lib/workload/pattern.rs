@@ -1,7 +1,9 @@
 use std::rc::Rc;
 
 use {Config, Result};
-use workload::Component;
+use list;
+use platform::ElementClassifier;
+use workload::{Component, Constraint};
 
 /// A workload pattern.
 #[derive(Clone, Debug)]
@@ -22,11 +24,19 @@ pub struct Content {
     pub time_step: f64,
     /// The components.
     pub components: Vec<Component>,
+    /// The placement constraints.
+    pub constraint: Constraint,
+    /// The intensity of the use of shared resources, such as the memory, which
+    /// determines the interference with other jobs.
+    pub intensity: f64,
 }
 
 impl Pattern {
     /// Create a pattern.
-    pub fn new(config: &Config) -> Result<Pattern> {
+    ///
+    /// Apart from the dynamic power, only the dynamic metrics listed in the
+    /// `metrics` key of the configuration are read from the database.
+    pub fn new(config: &Config, classifier: &ElementClassifier) -> Result<Pattern> {
         let path = path!(config, "a workload-pattern database is required");
 
         let name = match config.get::<String>("name") {
@@ -36,7 +46,8 @@ impl Pattern {
         let time_step = *some!(config.get::<f64>("time_step"), "a time step is required");
 
         info!(target: "Workload", "Reading a pattern from {:?}...", &path);
-        let components = try!(Component::collect(&path));
+        let metrics = try!(list::read::<String>(config, "metrics", "name")).unwrap_or_default();
+        let components = try!(Component::collect(&path, classifier, &metrics));
 
         let component_count = components.len();
         if component_count == 0 {
@@ -46,6 +57,14 @@ impl Pattern {
         if step_count == 0 {
             raise!("found a workload pattern without dynamic-power data");
         }
+        let constraint = match config.branch("constraint") {
+            Some(ref config) => try!(Constraint::new(config)),
+            _ => Constraint::default(),
+        };
+        let intensity = *config.get::<f64>("intensity").unwrap_or(&1.0);
+        if intensity < 0.0 {
+            raise!("the intensity of a workload pattern should be nonnegative");
+        }
 
         Ok(Pattern(Rc::new(Content {
             name: name,
@@ -53,6 +72,8 @@ impl Pattern {
             step_count: step_count,
             time_step: time_step,
             components: components,
+            constraint: constraint,
+            intensity: intensity,
         })))
     }
 
@@ -1,6 +1,7 @@
 use probability::distribution::{Categorical, Sample};
 
 use {Config, Result, Source};
+use platform::ElementClassifier;
 use workload::{Pattern, Workload};
 
 /// A workload model that chooses workload patterns randomly.
@@ -12,11 +13,12 @@ pub struct Random {
 
 impl Random {
     /// Create a model.
-    pub fn new(config: &Config, source: Source) -> Result<Random> {
+    pub fn new(config: &Config, classifier: &ElementClassifier, source: Source)
+               -> Result<Random> {
         let mut patterns = vec![];
         if let Some(ref configs) = config.forest("patterns") {
             for config in configs {
-                patterns.push(try!(Pattern::new(config)));
+                patterns.push(try!(Pattern::new(config, classifier)));
             }
         }
         let count = patterns.len();